@@ -3,7 +3,9 @@
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
-    @location(1) data: u32,
+    @location(1) uvs: vec2<f32>,
+    @location(2) data: u32,
+    @location(3) tint: vec4<f32>,
 }
 
 struct VertexOutput {
@@ -11,6 +13,9 @@ struct VertexOutput {
     @location(0) color_intensity: f32,
     @location(1) uvs: vec2<f32>,
     @location(2) texture_index: u32,
+    @location(3) tint: vec4<f32>,
+    @location(4) world_position: vec3<f32>,
+    @location(5) block_light: f32,
 }
 
 @group(2) @binding(0)
@@ -19,22 +24,28 @@ var<uniform> mesh: Mesh;
 @vertex
 fn vertex(in: VertexInput) -> VertexOutput {
     var out: VertexOutput;
-    out.clip_position = view.view_proj * mesh.model * vec4<f32>(in.position, 1.0);
+    let world_position = mesh.model * vec4<f32>(in.position, 1.0);
+    out.clip_position = view.view_proj * world_position;
+    out.world_position = world_position.xyz;
 
-    var color_intensity: f32 = f32((in.data >> 2u) & 5u) / 5.0;
+    var color_intensity: f32 = f32(in.data & 7u) / 5.0;
     if color_intensity < 0.4 {
         color_intensity = 0.4;
     }
 
     out.color_intensity = color_intensity;
 
-    var uvs: vec2<f32> = vec2<f32>(
-        f32((in.data >> 1u) & 1u),
-        f32(in.data & 1u),
-    );
-
-    out.uvs = uvs;
-    out.texture_index = (in.data >> 6u) & 255u;
+    out.uvs = in.uvs;
+    out.tint = in.tint;
+    // Animated textures step through consecutive layers, so chunks don't need to be remeshed.
+    let frames = ((in.data >> 11u) & 15u) + 1u;
+    let frame_time = f32((in.data >> 15u) & 63u) / 20.0;
+    var frame = 0u;
+    if frames > 1u {
+        frame = u32(globals.time / frame_time) % frames;
+    }
+    out.texture_index = ((in.data >> 3u) & 255u) + frame;
+    out.block_light = f32((in.data >> 21u) & 15u) / 15.0;
 
     return out;
 }
@@ -43,8 +54,42 @@ fn vertex(in: VertexInput) -> VertexOutput {
 var texture: texture_2d_array<f32>;
 @group(1) @binding(1)
 var texture_sampler: sampler;
+@group(1) @binding(2)
+var<uniform> alpha_cutoff: f32;
+
+struct Fog {
+    color: vec4<f32>,
+    start: f32,
+    end: f32,
+    density: f32,
+    mode: u32,
+}
+
+@group(1) @binding(3)
+var<uniform> fog: Fog;
+// Sky light scale from the time of day, block light isn't affected by it.
+@group(1) @binding(4)
+var<uniform> sky_brightness: f32;
+
+// How much of the fog colour covers a fragment at `distance` from the camera.
+fn fog_factor(distance: f32) -> f32 {
+    if fog.mode == 1u {
+        return clamp((distance - fog.start) / max(fog.end - fog.start, 0.0001), 0.0, 1.0);
+    }
+    if fog.mode == 2u {
+        return 1.0 - exp(-fog.density * distance);
+    }
+    return 0.0;
+}
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color_intensity * textureSample(texture, texture_sampler, in.uvs, i32(in.texture_index));
+    let color = textureSample(texture, texture_sampler, in.uvs, i32(in.texture_index));
+    if color.a < alpha_cutoff {
+        discard;
+    }
+    let light = max(sky_brightness, in.block_light);
+    let lit = light * in.color_intensity * in.tint.rgb * color.rgb;
+    let distance = length(in.world_position - view.world_position);
+    return vec4<f32>(mix(lit, fog.color.rgb, fog_factor(distance)), color.a);
 }
\ No newline at end of file
@@ -0,0 +1,26 @@
+#import bevy_pbr::mesh_view_bindings
+#import bevy_pbr::mesh_types
+
+struct OutlineMaterial {
+    color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> material: OutlineMaterial;
+
+@group(2) @binding(0)
+var<uniform> mesh: Mesh;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+}
+
+@vertex
+fn vertex(in: VertexInput) -> @builtin(position) vec4<f32> {
+    return view.view_proj * mesh.model * vec4<f32>(in.position, 1.0);
+}
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return material.color;
+}
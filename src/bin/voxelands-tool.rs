@@ -0,0 +1,256 @@
+use std::{
+    collections::HashMap,
+    env,
+    path::PathBuf,
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use glam::IVec3;
+use voxelands::{
+    data::{constants::*, VoxelType},
+    world::{storage, Chunk, World},
+};
+
+const USAGE: &str = "\
+Usage: voxelands-tool <command> [options]
+
+Commands:
+  pregen       Generate chunks around the origin and write them to disk
+  stats        Generate chunks and print voxel and timing statistics
+  heightmap    Generate chunks and write a grayscale heightmap PNG
+
+Options:
+  --seed <n>               World seed (default: 0)
+  --radius <n>             Horizontal radius in chunks (default: 8)
+  --vertical-radius <n>    Vertical radius in chunks (default: 4)
+  --out <path>             Output directory for pregen (default: world),
+                           output file for heightmap (default: heightmap.png)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Pregen,
+    Stats,
+    Heightmap,
+}
+
+#[derive(Debug)]
+struct Options {
+    command: Command,
+    seed: u64,
+    radius: i32,
+    vertical_radius: i32,
+    out: Option<PathBuf>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let command = match args.next().as_deref() {
+        Some("pregen") => Command::Pregen,
+        Some("stats") => Command::Stats,
+        Some("heightmap") => Command::Heightmap,
+        Some(other) => return Err(format!("unknown command `{other}`")),
+        None => return Err(String::from("missing command")),
+    };
+
+    let mut options = Options {
+        command,
+        seed: 0,
+        radius: 8,
+        vertical_radius: 4,
+        out: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for `{arg}`"))
+        };
+
+        match arg.as_str() {
+            "--seed" => options.seed = parse_value(&arg, &value()?)?,
+            "--radius" => options.radius = parse_value(&arg, &value()?)?,
+            "--vertical-radius" => options.vertical_radius = parse_value(&arg, &value()?)?,
+            "--out" => options.out = Some(PathBuf::from(value()?)),
+            _ => return Err(format!("unknown option `{arg}`")),
+        }
+    }
+
+    if options.radius < 1 {
+        return Err(String::from("`--radius` must be at least 1"));
+    }
+
+    if options.vertical_radius < 0 {
+        return Err(String::from("`--vertical-radius` must not be negative"));
+    }
+
+    Ok(options)
+}
+
+fn parse_value<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for `{arg}`"))
+}
+
+struct GeneratedChunk {
+    chunk: Chunk,
+    duration: Duration,
+}
+
+fn generate(options: &Options) -> Vec<GeneratedChunk> {
+    let positions =
+        World::chunks_within_radius(IVec3::ZERO, options.radius, options.vertical_radius);
+
+    positions
+        .into_iter()
+        .map(|position| {
+            let start = Instant::now();
+            let chunk = Chunk::generate_at(position, options.seed).unwrap();
+            GeneratedChunk {
+                chunk,
+                duration: start.elapsed(),
+            }
+        })
+        .collect()
+}
+
+fn print_timings(chunks: &[GeneratedChunk]) {
+    let mut durations: Vec<Duration> = chunks.iter().map(|c| c.duration).collect();
+    durations.sort_unstable();
+
+    let total: Duration = durations.iter().sum();
+    let average = total / durations.len().max(1) as u32;
+    let percentile = |p: f32| durations[((durations.len() - 1) as f32 * p) as usize];
+
+    println!("Chunks generated: {}", durations.len());
+    println!("Total generation time: {:.2?}", total);
+    println!(
+        "Per chunk: min {:.2?}, avg {:.2?}, p95 {:.2?}, max {:.2?}",
+        durations[0],
+        average,
+        percentile(0.95),
+        durations[durations.len() - 1]
+    );
+}
+
+fn pregen(options: &Options) -> Result<(), String> {
+    let directory = options
+        .out
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("world"));
+    let chunks = generate(options);
+
+    storage::save_seed(&directory, options.seed).map_err(|err| err.to_string())?;
+    for generated in &chunks {
+        storage::save_chunk(&directory, &generated.chunk).map_err(|err| err.to_string())?;
+    }
+
+    print_timings(&chunks);
+    println!("Wrote {} chunks to {}", chunks.len(), directory.display());
+    Ok(())
+}
+
+fn stats(options: &Options) -> Result<(), String> {
+    let chunks = generate(options);
+
+    let mut counts: HashMap<VoxelType, usize> = HashMap::new();
+    let mut empty_chunks = 0;
+    for generated in &chunks {
+        let mut empty = true;
+        for position in generated.chunk.iter_voxels() {
+            let voxel = generated.chunk.get_voxel(position);
+            *counts.entry(voxel).or_default() += 1;
+            empty &= voxel == VoxelType::Air;
+        }
+
+        if empty {
+            empty_chunks += 1;
+        }
+    }
+
+    println!("Seed: {}", options.seed);
+    print_timings(&chunks);
+    println!("Empty chunks: {empty_chunks}");
+    println!("Voxel counts:");
+
+    let total_voxels = chunks.len() * CHUNK_SIZE_CUBED;
+    for voxel in VoxelType::ALL {
+        let count = counts.get(&voxel).copied().unwrap_or_default();
+        println!(
+            "  {:<8} {:>12} ({:.2}%)",
+            format!("{voxel:?}"),
+            count,
+            count as f64 * 100.0 / total_voxels as f64
+        );
+    }
+
+    Ok(())
+}
+
+fn heightmap(options: &Options) -> Result<(), String> {
+    let path = options
+        .out
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("heightmap.png"));
+    let chunks = generate(options);
+
+    let size = (options.radius * 2 + 1) as u32 * CHUNK_SIZE as u32;
+    let origin = IVec3::splat(-options.radius * CHUNK_SIZE_I32);
+    let mut heights: Vec<Option<i32>> = vec![None; (size * size) as usize];
+
+    for generated in &chunks {
+        let chunk_origin = generated.chunk.world_position().as_ivec3();
+        for position in generated.chunk.iter_voxels() {
+            if generated.chunk.get_voxel(position) == VoxelType::Air {
+                continue;
+            }
+
+            let world_position = chunk_origin + position - origin;
+            let index = (world_position.z as u32 * size + world_position.x as u32) as usize;
+            let y = chunk_origin.y + position.y;
+            heights[index] = Some(heights[index].map_or(y, |h| h.max(y)));
+        }
+    }
+
+    let min = heights.iter().flatten().min().copied().unwrap_or_default();
+    let max = heights.iter().flatten().max().copied().unwrap_or_default();
+    let range = (max - min).max(1) as f32;
+
+    let image = image::GrayImage::from_fn(size, size, |x, z| {
+        let value = heights[(z * size + x) as usize]
+            .map(|h| ((h - min) as f32 / range * 255.0) as u8)
+            .unwrap_or_default();
+        image::Luma([value])
+    });
+
+    image.save(&path).map_err(|err| err.to_string())?;
+
+    print_timings(&chunks);
+    println!("Height range: {min}..={max}");
+    println!("Wrote {size}x{size} heightmap to {}", path.display());
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match options.command {
+        Command::Pregen => pregen(&options),
+        Command::Stats => stats(&options),
+        Command::Heightmap => heightmap(&options),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use glam::*;
+
+use super::voxel_face::VoxelFace;
+
+// Axis aligned box inside a voxel, in voxel local coordinates from 0.0 to 1.0.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BlockBox {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl BlockBox {
+    pub const FULL: BlockBox = BlockBox::new(Vec3::ZERO, Vec3::ONE);
+
+    pub const fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    // Whether the box touches the `face` side of the voxel.
+    pub fn touches_face(&self, face: VoxelFace) -> bool {
+        let normal = face.normal().as_vec3();
+        if normal.max_element() > 0.0 {
+            self.max.dot(normal) >= 1.0
+        } else {
+            self.min.dot(normal) >= 0.0
+        }
+    }
+
+    // Whether the box covers the whole `face` side of the voxel.
+    pub fn covers_face(&self, face: VoxelFace) -> bool {
+        let across = Vec3::ONE - face.normal().abs().as_vec3();
+        self.touches_face(face)
+            && (self.min * across).cmple(Vec3::ZERO).all()
+            && (self.max + (Vec3::ONE - across)).cmpge(Vec3::ONE).all()
+    }
+}
+
+const SLAB_BOXES: [BlockBox; 1] = [BlockBox::new(Vec3::ZERO, Vec3::new(1.0, 0.5, 1.0))];
+
+const STAIRS_BOXES: [BlockBox; 2] = [
+    BlockBox::new(Vec3::ZERO, Vec3::new(1.0, 0.5, 1.0)),
+    BlockBox::new(Vec3::new(0.0, 0.5, 0.0), Vec3::new(1.0, 1.0, 0.5)),
+];
+
+// Crossed quads have no boxes, so plants are targeted with a box around them instead.
+const CROSS_HIT_BOXES: [BlockBox; 1] = [BlockBox::new(
+    Vec3::new(0.125, 0.0, 0.125),
+    Vec3::new(0.875, 0.8125, 0.875),
+)];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BlockShape {
+    Cube,
+    // Lower half of a voxel.
+    Slab,
+    // A slab with a step along the back side.
+    Stairs,
+    // Two crossed quads, used for plants.
+    Cross,
+    // Any combination of boxes.
+    Model(&'static [BlockBox]),
+}
+
+impl BlockShape {
+    // Boxes making up the shape, crossed quads have none.
+    pub const fn boxes(self) -> &'static [BlockBox] {
+        match self {
+            BlockShape::Cube => &[BlockBox::FULL],
+            BlockShape::Slab => &SLAB_BOXES,
+            BlockShape::Stairs => &STAIRS_BOXES,
+            BlockShape::Cross => &[],
+            BlockShape::Model(boxes) => boxes,
+        }
+    }
+
+    // Boxes a ray has to hit to target the block, in block space.
+    pub const fn hit_boxes(self) -> &'static [BlockBox] {
+        match self {
+            BlockShape::Cross => &CROSS_HIT_BOXES,
+            _ => self.boxes(),
+        }
+    }
+
+    // A full face hides the face of the neighbouring voxel it touches.
+    pub fn is_face_full(self, face: VoxelFace) -> bool {
+        match self {
+            BlockShape::Cube => true,
+            BlockShape::Slab => face == VoxelFace::Bottom,
+            BlockShape::Stairs => matches!(face, VoxelFace::Bottom | VoxelFace::Back),
+            BlockShape::Cross => false,
+            BlockShape::Model(boxes) => boxes.iter().any(|b| b.covers_face(face)),
+        }
+    }
+}
+
+#[test]
+fn full_faces_match_boxes() {
+    use super::voxel_face::FACES;
+
+    for shape in [BlockShape::Cube, BlockShape::Slab] {
+        let model = BlockShape::Model(shape.boxes());
+        for face in FACES {
+            assert_eq!(shape.is_face_full(face), model.is_face_full(face));
+        }
+    }
+
+    let post = super::VoxelType::Fence.shape();
+    assert!(FACES.iter().all(|face| !post.is_face_full(*face)));
+}
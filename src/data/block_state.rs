@@ -0,0 +1,242 @@
+use glam::*;
+
+use super::voxel_face::{VoxelFace, FACES};
+
+const FACING_SHIFT: u16 = 0;
+const AXIS_SHIFT: u16 = 2;
+const HALF_SHIFT: u16 = 4;
+const LEVEL_SHIFT: u16 = 5;
+
+const FACING_MASK: u16 = 0b11 << FACING_SHIFT;
+const AXIS_MASK: u16 = 0b11 << AXIS_SHIFT;
+const HALF_MASK: u16 = 0b1 << HALF_SHIFT;
+const LEVEL_MASK: u16 = 0b1111 << LEVEL_SHIFT;
+const ROTATION_MASK: u16 = FACING_MASK | AXIS_MASK | HALF_MASK;
+
+// Horizontal direction a block is turned towards. Shapes are modelled facing north.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Facing {
+    // -Z
+    #[default]
+    North,
+    // +X
+    East,
+    // +Z
+    South,
+    // -X
+    West,
+}
+
+impl Facing {
+    pub const ALL: [Facing; 4] = [Facing::North, Facing::East, Facing::South, Facing::West];
+
+    // The facing closest to a horizontal direction.
+    pub fn from_direction(direction: Vec3) -> Facing {
+        if direction.x.abs() > direction.z.abs() {
+            if direction.x > 0.0 {
+                Facing::East
+            } else {
+                Facing::West
+            }
+        } else if direction.z > 0.0 {
+            Facing::South
+        } else {
+            Facing::North
+        }
+    }
+}
+
+// Axis the local Y axis of a block is aligned with, used by logs and pillars.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Axis {
+    X,
+    #[default]
+    Y,
+    Z,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    pub const fn from_face(face: VoxelFace) -> Axis {
+        match face {
+            VoxelFace::Left | VoxelFace::Right => Axis::X,
+            VoxelFace::Bottom | VoxelFace::Top => Axis::Y,
+            VoxelFace::Back | VoxelFace::Front => Axis::Z,
+        }
+    }
+}
+
+// Which half of the voxel slabs and stairs sit in. Top half blocks are turned upside down.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Half {
+    #[default]
+    Bottom,
+    Top,
+}
+
+// Per-voxel properties packed into 16 bits:
+// facing (2 bits), axis (2 bits), half (1 bit) and liquid level (4 bits).
+// Each voxel type only uses the properties that make sense for it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BlockState(u16);
+
+impl Default for BlockState {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl BlockState {
+    pub const DEFAULT: BlockState = BlockState((Axis::Y as u16) << AXIS_SHIFT);
+
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    const fn with_bits(self, mask: u16, shift: u16, value: u16) -> Self {
+        Self((self.0 & !mask) | ((value << shift) & mask))
+    }
+
+    pub const fn facing(self) -> Facing {
+        Facing::ALL[((self.0 & FACING_MASK) >> FACING_SHIFT) as usize]
+    }
+
+    pub const fn with_facing(self, facing: Facing) -> Self {
+        self.with_bits(FACING_MASK, FACING_SHIFT, facing as u16)
+    }
+
+    pub const fn axis(self) -> Axis {
+        match (self.0 & AXIS_MASK) >> AXIS_SHIFT {
+            0 => Axis::X,
+            2 => Axis::Z,
+            _ => Axis::Y,
+        }
+    }
+
+    pub const fn with_axis(self, axis: Axis) -> Self {
+        self.with_bits(AXIS_MASK, AXIS_SHIFT, axis as u16)
+    }
+
+    pub const fn half(self) -> Half {
+        if self.0 & HALF_MASK != 0 {
+            Half::Top
+        } else {
+            Half::Bottom
+        }
+    }
+
+    pub const fn with_half(self, half: Half) -> Self {
+        self.with_bits(HALF_MASK, HALF_SHIFT, half as u16)
+    }
+
+    pub const fn level(self) -> u8 {
+        ((self.0 & LEVEL_MASK) >> LEVEL_SHIFT) as u8
+    }
+
+    pub const fn with_level(self, level: u8) -> Self {
+        self.with_bits(LEVEL_MASK, LEVEL_SHIFT, level as u16)
+    }
+
+    // Rotates a direction from block space to world space: the local Y axis is first aligned
+    // with the axis, then the block is turned upside down for the top half and finally turned
+    // towards its facing. All steps are rotations, so the winding of faces is preserved.
+    pub fn rotate(self, direction: Vec3) -> Vec3 {
+        let mut d = match self.axis() {
+            Axis::X => Vec3::new(direction.y, -direction.x, direction.z),
+            Axis::Y => direction,
+            Axis::Z => Vec3::new(direction.x, -direction.z, direction.y),
+        };
+
+        if self.half() == Half::Top {
+            d = Vec3::new(-d.x, -d.y, d.z);
+        }
+
+        for _ in 0..self.facing() as u8 {
+            d = Vec3::new(-d.z, d.y, d.x);
+        }
+
+        d
+    }
+
+    // Maps a point in block space (0.0 to 1.0) to world space.
+    pub fn transform_point(self, point: Vec3) -> Vec3 {
+        self.rotate(point - 0.5) + 0.5
+    }
+
+    // Facing north along the Y axis in the bottom half, which is how almost all voxels are.
+    #[inline(always)]
+    pub const fn is_unrotated(self) -> bool {
+        self.0 & ROTATION_MASK == Self::DEFAULT.0 & ROTATION_MASK
+    }
+
+    pub fn to_world_face(self, face: VoxelFace) -> VoxelFace {
+        if self.is_unrotated() {
+            return face;
+        }
+
+        let normal = self.rotate(face.normal().as_vec3()).round().as_ivec3();
+        FACES
+            .into_iter()
+            .find(|face| face.normal() == normal)
+            .unwrap()
+    }
+
+    pub fn to_local_face(self, face: VoxelFace) -> VoxelFace {
+        if self.is_unrotated() {
+            return face;
+        }
+
+        FACES
+            .into_iter()
+            .find(|local| self.to_world_face(*local) == face)
+            .unwrap()
+    }
+}
+
+#[test]
+fn properties_are_packed_independently() {
+    let state = BlockState::DEFAULT
+        .with_facing(Facing::West)
+        .with_axis(Axis::Z)
+        .with_half(Half::Top)
+        .with_level(13);
+
+    assert_eq!(state.facing(), Facing::West);
+    assert_eq!(state.axis(), Axis::Z);
+    assert_eq!(state.half(), Half::Top);
+    assert_eq!(state.level(), 13);
+    assert_eq!(BlockState::from_bits(state.bits()), state);
+    assert_eq!(state.with_level(2).facing(), Facing::West);
+}
+
+#[test]
+fn rotations_map_faces() {
+    let east = BlockState::DEFAULT.with_facing(Facing::East);
+    assert_eq!(east.to_world_face(VoxelFace::Back), VoxelFace::Right);
+    assert_eq!(east.to_world_face(VoxelFace::Top), VoxelFace::Top);
+
+    let upside_down = BlockState::DEFAULT.with_half(Half::Top);
+    assert_eq!(upside_down.to_world_face(VoxelFace::Bottom), VoxelFace::Top);
+    assert_eq!(upside_down.to_world_face(VoxelFace::Back), VoxelFace::Back);
+
+    let sideways = BlockState::DEFAULT.with_axis(Axis::X);
+    assert_eq!(sideways.to_world_face(VoxelFace::Top), VoxelFace::Right);
+
+    let level = BlockState::DEFAULT.with_level(3);
+    assert!(level.is_unrotated());
+    assert!(!east.is_unrotated() && !upside_down.is_unrotated() && !sideways.is_unrotated());
+
+    for state in [east, upside_down, sideways] {
+        for face in FACES {
+            assert_eq!(state.to_local_face(state.to_world_face(face)), face);
+        }
+    }
+}
@@ -10,3 +10,6 @@ pub const Y_SHIFT: usize = 0;
 pub const X_MASK: usize = (CHUNK_SIZE - 1) << X_SHIFT;
 pub const Z_MASK: usize = (CHUNK_SIZE - 1) << Z_SHIFT;
 pub const Y_MASK: usize = CHUNK_SIZE - 1;
+
+// Liquid levels count down from the source as the liquid spreads; 0 means no liquid.
+pub const LIQUID_SOURCE_LEVEL: u8 = 8;
@@ -1,5 +1,7 @@
+pub mod block_shape;
+pub mod block_state;
 pub mod constants;
 pub mod voxel_face;
 mod voxel_type;
 
-pub use voxel_type::VoxelType;
+pub use voxel_type::{RenderLayer, TextureAnimation, TextureVariant, VoxelType};
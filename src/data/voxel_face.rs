@@ -1,4 +1,4 @@
-use bevy::math::*;
+use glam::*;
 
 #[repr(u8)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -92,6 +92,17 @@ impl VoxelFace {
     pub const fn vertex_positions(&self) -> [Vec3; 4] {
         FACE_VERTEX_POSITIONS[*self as usize]
     }
+
+    pub const fn opposite(&self) -> VoxelFace {
+        match self {
+            VoxelFace::Left => VoxelFace::Right,
+            VoxelFace::Right => VoxelFace::Left,
+            VoxelFace::Bottom => VoxelFace::Top,
+            VoxelFace::Top => VoxelFace::Bottom,
+            VoxelFace::Back => VoxelFace::Front,
+            VoxelFace::Front => VoxelFace::Back,
+        }
+    }
 }
 
 pub const FACE_UVS: [Vec2; 4] = [
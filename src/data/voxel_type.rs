@@ -1,17 +1,274 @@
-use super::voxel_face::VoxelFace;
+use glam::Vec3;
 
+use super::{
+    block_shape::{BlockBox, BlockShape},
+    block_state::{Axis, BlockState, Facing, Half},
+    constants::LIQUID_SOURCE_LEVEL,
+    voxel_face::VoxelFace,
+};
+
+// A texture layer that is picked for a face with a probability proportional to its weight.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TextureVariant {
+    pub layer: u32,
+    pub weight: u32,
+}
+
+impl TextureVariant {
+    pub const fn new(layer: u32, weight: u32) -> Self {
+        Self { layer, weight }
+    }
+}
+
+// Animated textures cycle through `frames` consecutive atlas layers, starting at the layer of the
+// face, showing each one for `frame_time` seconds.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextureAnimation {
+    pub frames: u32,
+    pub frame_time: f32,
+}
+
+impl TextureAnimation {
+    pub const fn new(frames: u32, frame_time: f32) -> Self {
+        Self { frames, frame_time }
+    }
+}
+
+const GRASS_TOP_VARIANTS: [TextureVariant; 2] =
+    [TextureVariant::new(0, 12), TextureVariant::new(14, 1)];
+const DIRT_VARIANTS: [TextureVariant; 2] = [TextureVariant::new(2, 6), TextureVariant::new(15, 1)];
+const STONE_VARIANTS: [TextureVariant; 2] = [TextureVariant::new(3, 4), TextureVariant::new(13, 1)];
+
+const FENCE_POST: [BlockBox; 1] = [BlockBox::new(
+    Vec3::new(0.375, 0.0, 0.375),
+    Vec3::new(0.625, 1.0, 0.625),
+)];
+
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum VoxelType {
     Air,
     Grass,
     Dirt,
     Stone,
+    Water,
+    Lava,
+    Glass,
+    Leaves,
+    StoneSlab,
+    StoneStairs,
+    TallGrass,
+    Fence,
+    Log,
+}
+
+// The sub-mesh a voxel is drawn in. Cutout voxels are either fully opaque or fully transparent
+// per pixel, translucent voxels are alpha blended.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RenderLayer {
+    Opaque,
+    Cutout,
+    Translucent,
 }
 
 impl VoxelType {
+    pub const ALL: [VoxelType; 13] = [
+        VoxelType::Air,
+        VoxelType::Grass,
+        VoxelType::Dirt,
+        VoxelType::Stone,
+        VoxelType::Water,
+        VoxelType::Lava,
+        VoxelType::Glass,
+        VoxelType::Leaves,
+        VoxelType::StoneSlab,
+        VoxelType::StoneStairs,
+        VoxelType::TallGrass,
+        VoxelType::Fence,
+        VoxelType::Log,
+    ];
+
+    #[inline(always)]
+    pub const fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub const fn from_id(id: u8) -> Option<VoxelType> {
+        if (id as usize) < VoxelType::ALL.len() {
+            Some(VoxelType::ALL[id as usize])
+        } else {
+            None
+        }
+    }
+
+    // Seconds needed to mine the block by hand.
+    pub const fn hardness(self) -> f32 {
+        match self {
+            VoxelType::Air => 0.0,
+            VoxelType::Grass => 0.6,
+            VoxelType::Dirt => 0.5,
+            VoxelType::Stone => 1.5,
+            VoxelType::Water | VoxelType::Lava => 0.0,
+            VoxelType::Glass => 0.3,
+            VoxelType::Leaves => 0.2,
+            VoxelType::StoneSlab | VoxelType::StoneStairs => 1.5,
+            VoxelType::TallGrass => 0.0,
+            VoxelType::Fence => 1.0,
+            VoxelType::Log => 1.2,
+        }
+    }
+
+    #[inline(always)]
+    pub const fn is_liquid(self) -> bool {
+        matches!(self, VoxelType::Water | VoxelType::Lava)
+    }
+
+    // Transparent voxels don't hide the faces of the voxels next to them.
+    #[inline(always)]
+    pub const fn is_transparent(self) -> bool {
+        !matches!(self.render_layer(), RenderLayer::Opaque) || self.is_liquid()
+    }
+
+    pub const fn render_layer(self) -> RenderLayer {
+        match self {
+            VoxelType::Air | VoxelType::Water | VoxelType::Glass => RenderLayer::Translucent,
+            VoxelType::Leaves | VoxelType::TallGrass => RenderLayer::Cutout,
+            _ => RenderLayer::Opaque,
+        }
+    }
+
+    // Solid voxels block movement.
+    #[inline(always)]
+    pub const fn is_solid(self) -> bool {
+        !matches!(self, VoxelType::Air)
+            && !self.is_liquid()
+            && !matches!(self.shape(), BlockShape::Cross)
+    }
+
+    // Pillars are aligned with an axis, their top and bottom texture is shown at both ends.
     #[inline(always)]
-    pub fn is_transparent(self) -> bool {
-        self == VoxelType::Air
+    pub const fn is_pillar(self) -> bool {
+        matches!(self, VoxelType::Log)
+    }
+
+    pub const fn shape(self) -> BlockShape {
+        match self {
+            VoxelType::StoneSlab => BlockShape::Slab,
+            VoxelType::StoneStairs => BlockShape::Stairs,
+            VoxelType::TallGrass => BlockShape::Cross,
+            VoxelType::Fence => BlockShape::Model(&FENCE_POST),
+            _ => BlockShape::Cube,
+        }
+    }
+
+    // Placing a block can overwrite replaceable voxels.
+    #[inline(always)]
+    pub const fn is_replaceable(self) -> bool {
+        !self.is_solid()
+    }
+
+    pub const fn default_state(self) -> BlockState {
+        if self.is_liquid() {
+            BlockState::DEFAULT.with_level(LIQUID_SOURCE_LEVEL)
+        } else {
+            BlockState::DEFAULT
+        }
+    }
+
+    // State of a block placed against the `face` of another block, by someone looking in
+    // `direction`. Stairs rise away from the player and logs point out of the clicked face.
+    pub fn placement_state(self, face: VoxelFace, direction: Vec3) -> BlockState {
+        let half = if face == VoxelFace::Bottom {
+            Half::Top
+        } else {
+            Half::Bottom
+        };
+
+        match self.shape() {
+            BlockShape::Slab => self.default_state().with_half(half),
+            BlockShape::Stairs => self
+                .default_state()
+                .with_half(half)
+                .with_facing(Facing::from_direction(direction)),
+            _ if self.is_pillar() => self.default_state().with_axis(Axis::from_face(face)),
+            _ => self.default_state(),
+        }
+    }
+
+    // How much the level drops for each voxel a liquid spreads sideways.
+    pub const fn liquid_decay(self) -> u8 {
+        match self {
+            VoxelType::Lava => 2,
+            _ => 1,
+        }
+    }
+
+    // Light given off by the voxel, from 0 to 15. Emitted light isn't dimmed at night.
+    pub const fn light_emission(self) -> u8 {
+        match self {
+            VoxelType::Lava => 15,
+            _ => 0,
+        }
+    }
+
+    // Texture variants of a face, empty when the face only has the layer from `get_face_index`.
+    pub const fn texture_variants(self, face: VoxelFace) -> &'static [TextureVariant] {
+        match self {
+            VoxelType::Grass if face.is_side_face() => &[],
+            VoxelType::Grass => match face {
+                VoxelFace::Top => &GRASS_TOP_VARIANTS,
+                _ => &DIRT_VARIANTS,
+            },
+            VoxelType::Dirt => &DIRT_VARIANTS,
+            VoxelType::Stone | VoxelType::StoneSlab | VoxelType::StoneStairs => &STONE_VARIANTS,
+            _ => &[],
+        }
+    }
+
+    // Tinted faces use a grayscale texture that is coloured by the biome.
+    pub const fn is_tinted(self, face: VoxelFace) -> bool {
+        match self {
+            VoxelType::Grass => matches!(face, VoxelFace::Top),
+            VoxelType::Leaves | VoxelType::TallGrass => true,
+            _ => false,
+        }
+    }
+
+    // Textures without a clear up direction can be rotated randomly to hide tiling.
+    pub const fn has_random_rotation(self, face: VoxelFace) -> bool {
+        match self {
+            VoxelType::Grass => !face.is_side_face(),
+            VoxelType::Dirt | VoxelType::Stone | VoxelType::Leaves => true,
+            _ => false,
+        }
+    }
+
+    pub const fn texture_animation(self) -> Option<TextureAnimation> {
+        match self {
+            VoxelType::Water => Some(TextureAnimation::new(4, 0.25)),
+            VoxelType::Lava => Some(TextureAnimation::new(4, 0.6)),
+            _ => None,
+        }
+    }
+
+    // Picks the texture layer of a face from its variants. `hash` should be derived from the
+    // world position so the choice is stable between remeshes.
+    pub fn face_texture(self, face: VoxelFace, hash: u64) -> u32 {
+        let variants = self.texture_variants(face);
+        let total_weight: u32 = variants.iter().map(|variant| variant.weight).sum();
+        if total_weight == 0 {
+            return self.get_face_index(face);
+        }
+
+        let mut pick = (hash % total_weight as u64) as u32;
+        for variant in variants {
+            if pick < variant.weight {
+                return variant.layer;
+            }
+            pick -= variant.weight;
+        }
+
+        unreachable!()
     }
 
     pub const fn get_face_index(&self, face: VoxelFace) -> u32 {
@@ -22,7 +279,17 @@ impl VoxelType {
                 _ => 2,
             },
             VoxelType::Dirt => 2,
-            VoxelType::Stone => 3,
+            VoxelType::Stone | VoxelType::StoneSlab | VoxelType::StoneStairs => 3,
+            VoxelType::Water => 16,
+            VoxelType::Lava => 20,
+            VoxelType::Glass => 7,
+            VoxelType::Leaves => 8,
+            VoxelType::TallGrass => 9,
+            VoxelType::Fence => 10,
+            VoxelType::Log => match face {
+                VoxelFace::Bottom | VoxelFace::Top => 11,
+                _ => 12,
+            },
             _ => 0,
         }
     }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use bevy::{
     diagnostic::{Diagnostic, DiagnosticId, Diagnostics, EntityCountDiagnosticsPlugin},
     pbr::wireframe::{WireframeConfig, WireframePlugin},
@@ -5,15 +7,38 @@ use bevy::{
 };
 use bevy_inspector_egui::{bevy_egui::EguiContext, quick::WorldInspectorPlugin};
 
-use crate::world::World;
+use crate::{
+    data::constants::*,
+    rendering::{box_outline_mesh, OutlineMaterial},
+    world::World,
+};
+
+use super::{
+    diagnostic_percentile, Action, ActionState, CameraState, ChunkComponent,
+    ChunkDiagnosticsPlugin, ChunkState,
+};
 
-use super::CameraState;
+const CHUNK_BORDER_RADIUS: i32 = 4;
 
 #[derive(Resource, Default)]
 pub struct DebugSettings {
     wireframe_rendering: bool,
+    chunk_borders: bool,
+    pending_chunks_only: bool,
+}
+
+#[derive(Component)]
+struct ChunkBorder;
+
+#[derive(Resource)]
+struct ChunkBorderAssets {
+    mesh: Handle<Mesh>,
+    materials: HashMap<ChunkState, Handle<OutlineMaterial>>,
 }
 
+#[derive(Resource, Default)]
+struct ChunkBorders(HashMap<IVec3, Entity>);
+
 pub struct DebugPlugin;
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
@@ -21,9 +46,13 @@ impl Plugin for DebugPlugin {
             .add_plugin(WorldInspectorPlugin)
             .add_plugin(EntityCountDiagnosticsPlugin)
             .init_resource::<DebugSettings>()
+            .init_resource::<ChunkBorders>()
             .insert_resource(Diagnostics::default())
             .add_startup_system(setup)
+            .add_startup_system(setup_chunk_borders)
             .add_system(toggle_wireframe_mode)
+            .add_system(toggle_chunk_borders)
+            .add_system(update_chunk_borders.after(toggle_chunk_borders))
             .add_system(diagnostic_system)
             .add_system(update_ui)
             .add_system(ui_camera);
@@ -31,16 +60,112 @@ impl Plugin for DebugPlugin {
 }
 
 fn toggle_wireframe_mode(
-    keys: Res<Input<KeyCode>>,
+    actions: Res<ActionState>,
     mut wireframe_config: ResMut<WireframeConfig>,
     mut settings: ResMut<DebugSettings>,
 ) {
-    if keys.just_pressed(KeyCode::F1) {
+    if actions.just_pressed(Action::ToggleWireframe) {
         settings.wireframe_rendering = !settings.wireframe_rendering;
         wireframe_config.global = settings.wireframe_rendering;
     }
 }
 
+fn toggle_chunk_borders(actions: Res<ActionState>, mut settings: ResMut<DebugSettings>) {
+    if actions.just_pressed(Action::ToggleChunkBorders) {
+        settings.chunk_borders = !settings.chunk_borders;
+    }
+}
+
+fn setup_chunk_borders(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<OutlineMaterial>>,
+) {
+    let colors = [
+        (ChunkState::Queued, Color::GRAY),
+        (ChunkState::Generating, Color::YELLOW),
+        (ChunkState::Generated, Color::ORANGE),
+        (ChunkState::NeighboursReady, Color::CYAN),
+        (ChunkState::Meshing, Color::BLUE),
+        (ChunkState::Meshed, Color::GREEN),
+    ];
+
+    commands.insert_resource(ChunkBorderAssets {
+        mesh: meshes.add(box_outline_mesh(Vec3::splat(CHUNK_SIZE_I32 as f32 * 0.5))),
+        materials: colors
+            .into_iter()
+            .map(|(state, color)| (state, materials.add(OutlineMaterial { color })))
+            .collect(),
+    });
+}
+
+// Keeps one outline entity per chunk around the camera, coloured by the chunk's state.
+fn update_chunk_borders(
+    mut commands: Commands,
+    settings: Res<DebugSettings>,
+    assets: Res<ChunkBorderAssets>,
+    mut borders: ResMut<ChunkBorders>,
+    camera: Query<&Transform, With<CameraState>>,
+    chunks: Query<(&ChunkComponent, &ChunkState)>,
+    mut border_materials: Query<&mut Handle<OutlineMaterial>, With<ChunkBorder>>,
+) {
+    let camera_chunk = World::world_to_chunk_position(camera.single().translation.as_ivec3());
+
+    let mut visible = HashMap::new();
+    if settings.chunk_borders {
+        for (chunk, state) in &chunks {
+            let offset = (chunk.0 - camera_chunk).abs();
+            if offset.max_element() > CHUNK_BORDER_RADIUS {
+                continue;
+            }
+
+            if !settings.pending_chunks_only || state.is_pending() {
+                visible.insert(chunk.0, *state);
+            }
+        }
+    }
+
+    borders.0.retain(|position, entity| {
+        let keep = visible.contains_key(position);
+        if !keep {
+            commands.entity(*entity).despawn();
+        }
+        keep
+    });
+
+    for (position, state) in visible {
+        let material = assets.materials[&state].clone();
+
+        match borders.0.get(&position) {
+            Some(entity) => {
+                if let Ok(mut handle) = border_materials.get_mut(*entity) {
+                    if *handle != material {
+                        *handle = material;
+                    }
+                }
+            }
+            None => {
+                // Voxels are centered on their position, so chunks span half a voxel less.
+                let center =
+                    (position * CHUNK_SIZE_I32).as_vec3() + CHUNK_SIZE_I32 as f32 * 0.5 - 0.5;
+
+                let entity = commands
+                    .spawn((
+                        ChunkBorder,
+                        MaterialMeshBundle {
+                            mesh: assets.mesh.clone(),
+                            material,
+                            transform: Transform::from_translation(center),
+                            ..Default::default()
+                        },
+                    ))
+                    .id();
+                borders.0.insert(position, entity);
+            }
+        }
+    }
+}
+
 const DIAGNOSTIC_FPS: DiagnosticId = DiagnosticId::from_u128(0);
 const DIAGNOSTIC_FRAME_TIME: DiagnosticId = DiagnosticId::from_u128(1);
 
@@ -60,7 +185,12 @@ fn diagnostic_system(mut diagnostics: ResMut<Diagnostics>, time: Res<Time>) {
     diagnostics.add_measurement(DIAGNOSTIC_FPS, || 1.0 / delta_seconds);
 }
 
-fn update_ui(mut ctx: ResMut<EguiContext>, diagnostics: Res<Diagnostics>, world: Res<World>) {
+fn update_ui(
+    mut ctx: ResMut<EguiContext>,
+    mut settings: ResMut<DebugSettings>,
+    diagnostics: Res<Diagnostics>,
+    world: Res<World>,
+) {
     let egui_context = ctx.ctx_mut().clone();
 
     egui::Window::new("UI").show(&egui_context, |ui| {
@@ -90,7 +220,54 @@ fn update_ui(mut ctx: ResMut<EguiContext>, diagnostics: Res<Diagnostics>, world:
                 .get_measurement(DIAGNOSTIC_FPS)
                 .map(|d| d.value.round() as i32)
                 .unwrap_or_default()
-        ))
+        ));
+
+        ui.heading("Chunks");
+
+        ui.checkbox(&mut settings.chunk_borders, "Chunk borders (F2)");
+        ui.checkbox(
+            &mut settings.pending_chunks_only,
+            "Only chunks with pending work",
+        );
+
+        let value = |id| {
+            diagnostics
+                .get_measurement(id)
+                .map(|d| d.value)
+                .unwrap_or_default()
+        };
+
+        for (state, id) in ChunkDiagnosticsPlugin::STATES {
+            ui.label(format!("{state:?}: {}", value(id)));
+        }
+        ui.label(format!("Dirty: {}", value(ChunkDiagnosticsPlugin::DIRTY)));
+
+        for (name, id) in [
+            ("Generation", ChunkDiagnosticsPlugin::GENERATION_TIME),
+            ("Meshing", ChunkDiagnosticsPlugin::MESHING_TIME),
+        ] {
+            let Some(diagnostic) = diagnostics.get(id) else {
+                continue;
+            };
+
+            ui.label(format!(
+                "{name}: avg {:.2}ms, p50 {:.2}ms, p95 {:.2}ms, p99 {:.2}ms",
+                diagnostic.average().unwrap_or_default(),
+                diagnostic_percentile(diagnostic, 0.5).unwrap_or_default(),
+                diagnostic_percentile(diagnostic, 0.95).unwrap_or_default(),
+                diagnostic_percentile(diagnostic, 0.99).unwrap_or_default(),
+            ));
+        }
+
+        ui.label(format!(
+            "Vertices: {}, Triangles: {}",
+            value(ChunkDiagnosticsPlugin::VERTICES),
+            value(ChunkDiagnosticsPlugin::TRIANGLES)
+        ));
+        ui.label(format!(
+            "Voxel memory: {:.1}MiB",
+            value(ChunkDiagnosticsPlugin::VOXEL_MEMORY)
+        ));
     });
 }
 
@@ -1,17 +1,30 @@
-use bevy::{input::mouse::MouseMotion, prelude::*, window::CursorGrabMode};
+use bevy::{
+    input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+    prelude::*,
+    window::CursorGrabMode,
+};
 use bevy_inspector_egui::bevy_egui::EguiContext;
 
-use crate::world::World;
+use super::{Action, ActionState, ChunkLoader, MovementMode, PlayerController, Settings};
 
 #[derive(Component)]
 pub struct CameraState {
     pub sensibility: f32,
     pub fov: f32,
     pub speed: f32,
+    pub min_speed: f32,
+    pub max_speed: f32,
+    pub run_multiplier: f32,
+    pub acceleration: f32,
+    pub damping: f32,
+    pub scroll_speed_factor: f32,
+    pub roll_speed: f32,
+    pub look_speed: f32,
+    pub rotation_smoothing: f32,
     pub rotation_velocity: Vec2,
     pub rotation: Vec2,
+    pub roll: f32,
     pub velocity: Vec3,
-    pub should_load_chunks: bool,
 }
 
 impl Default for CameraState {
@@ -20,10 +33,19 @@ impl Default for CameraState {
             sensibility: 70.0,
             fov: 70.0,
             speed: 120.0,
+            min_speed: 2.0,
+            max_speed: 2000.0,
+            run_multiplier: 10.0,
+            acceleration: 10.0,
+            damping: 6.0,
+            scroll_speed_factor: 1.15,
+            roll_speed: 1.5,
+            look_speed: 2.5,
+            rotation_smoothing: 0.5,
             rotation_velocity: Vec2::ZERO,
             rotation: Vec2::ZERO,
+            roll: 0.0,
             velocity: Vec3::ZERO,
-            should_load_chunks: true,
         }
     }
 }
@@ -31,73 +53,110 @@ pub struct CameraPlugin;
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<MouseMotion>()
+            .add_event::<MouseWheel>()
             .add_startup_system(build_camera)
             .add_system(rotate_camera)
             .add_system(cursor_grab)
-            .add_system(move_camera);
+            .add_system(adjust_camera_speed)
+            .add_system(move_camera.after(adjust_camera_speed));
     }
 }
 
-fn build_camera(mut commands: Commands) {
+fn build_camera(mut commands: Commands, settings: Res<Settings>) {
     commands
         .spawn(CameraState::default())
         .insert(Camera3dBundle {
             transform: Transform::from_xyz(0.0, 32.0, 0.0).looking_at(Vec3::ZERO, Vec3::Y),
             projection: Projection::Perspective(PerspectiveProjection {
-                fov: CameraState::default().fov,
+                fov: CameraState::default().fov.to_radians(),
                 ..Default::default()
             }),
             ..Default::default()
+        })
+        .insert(PlayerController::default())
+        .insert(ChunkLoader {
+            radius: settings.view_distance,
+            vertical_radius: settings.vertical_view_distance,
         });
 }
 
 fn move_camera(
     time: Res<Time>,
-    keys: Res<Input<KeyCode>>,
-    mut query: Query<(&mut Transform, &mut CameraState)>,
+    actions: Res<ActionState>,
+    mut query: Query<(&mut Transform, &mut CameraState, &PlayerController)>,
 ) {
-    let (mut transform, mut state) = query.single_mut();
-    let is_running = keys.pressed(KeyCode::LShift);
-    let start_chunk_position = World::world_to_chunk_position(transform.translation.as_ivec3());
-
-    let forward = transform.forward().normalize_or_zero();
-    let right = forward.cross(Vec3::Y);
-
-    let mut direction = Vec3::ZERO;
-    if keys.pressed(KeyCode::W) {
-        direction += forward;
-    } else if keys.pressed(KeyCode::S) {
-        direction -= forward;
+    let (mut transform, mut state, controller) = query.single_mut();
+    if controller.mode != MovementMode::Fly {
+        return;
     }
 
-    if keys.pressed(KeyCode::A) {
-        direction -= right;
-    } else if keys.pressed(KeyCode::D) {
-        direction += right;
-    }
+    let is_running = actions.pressed(Action::Run);
+
+    let direction = transform.forward() * actions.axis(Action::MoveForward, Action::MoveBackward)
+        + transform.right() * actions.axis(Action::MoveRight, Action::MoveLeft)
+        + transform.up() * actions.axis(Action::MoveUp, Action::MoveDown);
+
+    let speed = state.speed
+        * if is_running {
+            state.run_multiplier
+        } else {
+            1.0
+        };
+    let target_velocity = direction.clamp_length_max(1.0) * speed;
+
+    // Exponential smoothing keeps acceleration and damping independent of the frame rate.
+    let rate = if direction == Vec3::ZERO {
+        state.damping
+    } else {
+        state.acceleration
+    };
+    let t = 1.0 - (-rate * time.delta_seconds()).exp();
+    state.velocity = state.velocity.lerp(target_velocity, t);
 
-    let velocity =
-        direction.normalize_or_zero() * state.speed * if is_running { 10.0 } else { 1.0 };
-    state.velocity = velocity;
+    let roll = actions.axis(Action::RollLeft, Action::RollRight);
+    state.roll += roll * state.roll_speed * time.delta_seconds();
 
-    transform.translation += velocity * time.delta_seconds();
+    transform.translation += state.velocity * time.delta_seconds();
+}
+
+fn adjust_camera_speed(
+    actions: Res<ActionState>,
+    mut scroll_events: EventReader<MouseWheel>,
+    mut query: Query<(&mut CameraState, &PlayerController)>,
+) {
+    let (mut state, controller) = query.single_mut();
+
+    let mut scroll = 0.0;
+    for ev in scroll_events.iter() {
+        scroll += match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            MouseScrollUnit::Pixel => ev.y / 100.0,
+        };
+    }
 
-    let end_chunk_position = World::world_to_chunk_position(transform.translation.as_ivec3());
-    if start_chunk_position != end_chunk_position {
-        state.should_load_chunks = true;
+    if scroll == 0.0
+        || controller.mode != MovementMode::Fly
+        || !actions.pressed(Action::AdjustSpeed)
+    {
+        return;
     }
+
+    state.speed = (state.speed * state.scroll_speed_factor.powf(scroll))
+        .clamp(state.min_speed, state.max_speed);
 }
 
 fn rotate_camera(
+    time: Res<Time>,
+    actions: Res<ActionState>,
     mut windows: ResMut<Windows>,
-    mut query: Query<(&mut Transform, &mut CameraState)>,
+    mut query: Query<(&mut Transform, &mut CameraState, &PlayerController)>,
     mut motion_event: EventReader<MouseMotion>,
 ) {
     let Some(window) = windows.get_primary_mut() else {
         return;
     };
 
-    let (mut camera_transform, mut camera_state) = query.single_mut();
+    let (mut camera_transform, mut camera_state, controller) = query.single_mut();
 
     for ev in motion_event.iter() {
         match window.cursor_grab_mode() {
@@ -106,7 +165,7 @@ fn rotate_camera(
                 let window_scale = window.height().min(window.width());
 
                 let mouse_rotation =
-                    ev.delta * (camera_state.sensibility * 0.000001) * window_scale;
+                    ev.delta * (camera_state.sensibility * 0.000002) * window_scale;
                 camera_state.rotation_velocity.y += mouse_rotation.y.to_radians();
                 camera_state.rotation_velocity.x += mouse_rotation.x.to_radians();
 
@@ -115,21 +174,38 @@ fn rotate_camera(
         }
     }
 
-    let velocity = camera_state.rotation_velocity;
+    let look = Vec2::new(
+        actions.axis(Action::LookRight, Action::LookLeft),
+        actions.axis(Action::LookDown, Action::LookUp),
+    );
+    let look_speed = camera_state.look_speed;
+    camera_state.rotation_velocity += look * look_speed * time.delta_seconds();
+
+    // Apply a frame-rate independent share of the pending rotation, where `rotation_smoothing`
+    // is the share left over after 1/60th of a second.
+    let t = 1.0
+        - camera_state
+            .rotation_smoothing
+            .powf(time.delta_seconds() * 60.0);
+    let velocity = camera_state.rotation_velocity * t;
     camera_state.rotation -= velocity;
-    camera_state.rotation_velocity = camera_state.rotation_velocity.lerp(Vec2::ZERO, 0.5);
+    camera_state.rotation_velocity -= velocity;
 
     camera_state.rotation.y = camera_state.rotation.y.clamp(-1.54, 1.54);
 
+    if controller.mode != MovementMode::Fly {
+        camera_state.roll = 0.0;
+    }
+
     camera_transform.rotation = Quat::from_axis_angle(Vec3::Y, camera_state.rotation.x)
-        * Quat::from_axis_angle(Vec3::X, camera_state.rotation.y);
+        * Quat::from_axis_angle(Vec3::X, camera_state.rotation.y)
+        * Quat::from_axis_angle(Vec3::Z, camera_state.roll);
 }
 
 fn cursor_grab(
     mut windows: ResMut<Windows>,
     mut egui: ResMut<EguiContext>,
-    keys: Res<Input<KeyCode>>,
-    mouse_button: Res<Input<MouseButton>>,
+    actions: Res<ActionState>,
 ) {
     let Some(window) = windows.get_primary_mut() else {
         return;
@@ -141,9 +217,9 @@ fn cursor_grab(
 
     let egui_context = egui.ctx_mut();
 
-    if !egui_context.wants_pointer_input() && mouse_button.just_pressed(MouseButton::Left) {
+    if !egui_context.wants_pointer_input() && actions.just_pressed(Action::GrabCursor) {
         toggle_grab_cursor(window, true);
-    } else if keys.just_pressed(KeyCode::Escape) {
+    } else if actions.just_pressed(Action::ReleaseCursor) {
         toggle_grab_cursor(window, false);
     }
 }
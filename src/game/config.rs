@@ -0,0 +1,46 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+pub const CONFIG_DIRECTORY: &str = "config";
+
+pub fn config_path(file_name: &str) -> PathBuf {
+    Path::new(CONFIG_DIRECTORY).join(file_name)
+}
+
+pub fn save_config<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let contents = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, contents)
+}
+
+// Falls back to the default (and writes it out) when the file is missing, and to the default
+// without overwriting the file when it can't be parsed.
+pub fn load_config_or_default<T: DeserializeOwned + Serialize + Default>(path: &Path) -> T {
+    match fs::read_to_string(path) {
+        Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+            warn!("Failed to parse {}: {err}, using defaults", path.display());
+            T::default()
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            let value = T::default();
+            if let Err(err) = save_config(path, &value) {
+                warn!("Failed to write {}: {err}", path.display());
+            }
+            value
+        }
+        Err(err) => {
+            warn!("Failed to read {}: {err}, using defaults", path.display());
+            T::default()
+        }
+    }
+}
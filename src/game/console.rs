@@ -0,0 +1,553 @@
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use bevy::{ecs::world::World as EcsWorld, prelude::*, window::CursorGrabMode};
+use bevy_inspector_egui::bevy_egui::EguiContext;
+
+use crate::{
+    data::{constants::CHUNK_SIZE_I32, VoxelType},
+    world::{storage, Chunk, FluidSimulation, World},
+};
+
+use super::{
+    Action, ActionState, CameraState, DirtyChunks, Settings, WorldSaveDirectory,
+    VERTICAL_VIEW_DISTANCE_RANGE, VIEW_DISTANCE_RANGE,
+};
+
+const MAX_LOG_LINES: usize = 200;
+const MAX_FILL_VOLUME: i64 = 1 << 20;
+
+// Receives the arguments following the command name. The returned message is printed to the
+// console, errors are prefixed so they stand out.
+pub type ConsoleCommandHandler = fn(&mut EcsWorld, &[&str]) -> Result<String, String>;
+
+#[derive(Clone)]
+pub struct ConsoleCommand {
+    pub usage: &'static str,
+    pub handler: ConsoleCommandHandler,
+}
+
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(BTreeMap<&'static str, ConsoleCommand>);
+
+impl ConsoleCommands {
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        handler: ConsoleCommandHandler,
+    ) {
+        self.0.insert(name, ConsoleCommand { usage, handler });
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ConsoleCommand> {
+        self.0.get(name)
+    }
+
+    pub fn names_starting_with<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a str> {
+        self.0
+            .keys()
+            .copied()
+            .filter(move |name| name.starts_with(prefix))
+    }
+}
+
+pub trait ConsoleAppExt {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        handler: ConsoleCommandHandler,
+    ) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        handler: ConsoleCommandHandler,
+    ) -> &mut Self {
+        self.init_resource::<ConsoleCommands>();
+        self.world
+            .resource_mut::<ConsoleCommands>()
+            .register(name, usage, handler);
+        self
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    log: Vec<String>,
+    history: Vec<String>,
+    history_index: Option<usize>,
+    pending: Vec<String>,
+}
+
+impl Console {
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        if self.log.len() > MAX_LOG_LINES {
+            self.log.drain(..self.log.len() - MAX_LOG_LINES);
+        }
+    }
+
+    pub fn submit(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        self.history_index = None;
+        self.pending.push(line);
+    }
+
+    fn browse_history(&mut self, offset: isize) {
+        if self.history.is_empty() {
+            return;
+        }
+
+        let index = match self.history_index {
+            Some(index) => index as isize + offset,
+            None if offset < 0 => self.history.len() as isize - 1,
+            None => return,
+        };
+
+        if index >= self.history.len() as isize {
+            self.history_index = None;
+            self.input.clear();
+        } else {
+            let index = index.max(0) as usize;
+            self.history_index = Some(index);
+            self.input = self.history[index].clone();
+        }
+    }
+}
+
+pub struct ConsolePlugin;
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleCommands>()
+            .add_console_command("help", "help", help)
+            .add_console_command("tp", "tp <x> <y> <z>", teleport)
+            .add_console_command("setblock", "setblock <x> <y> <z> <block>", set_block)
+            .add_console_command("fill", "fill <x1> <y1> <z1> <x2> <y2> <z2> <block>", fill)
+            .add_console_command("seed", "seed", seed)
+            .add_console_command(
+                "viewdistance",
+                "viewdistance [distance] [vertical distance]",
+                view_distance,
+            )
+            .add_console_command("regen", "regen chunk [x y z]", regenerate)
+            .add_console_command("save", "save", save)
+            .add_system(toggle_console)
+            .add_system(console_ui.after(toggle_console))
+            .add_system(execute_console_commands.after(console_ui));
+    }
+}
+
+fn toggle_console(
+    actions: Res<ActionState>,
+    mut console: ResMut<Console>,
+    mut windows: ResMut<Windows>,
+) {
+    if !actions.just_pressed(Action::ToggleConsole) {
+        return;
+    }
+
+    console.open = !console.open;
+    if console.open {
+        if let Some(window) = windows.get_primary_mut() {
+            window.set_cursor_grab_mode(CursorGrabMode::None);
+            window.set_cursor_visibility(true);
+        }
+    }
+}
+
+fn console_ui(
+    mut ctx: ResMut<EguiContext>,
+    actions: Res<ActionState>,
+    mut console: ResMut<Console>,
+    commands: Res<ConsoleCommands>,
+) {
+    if !console.open {
+        return;
+    }
+
+    // Whatever the toggle key typed on the frame the console opened shouldn't end up in the
+    // command line, whichever key it's bound to.
+    let input_before_open = actions
+        .just_pressed(Action::ToggleConsole)
+        .then(|| console.input.clone());
+
+    let egui_context = ctx.ctx_mut().clone();
+    let mut open = true;
+
+    egui::Window::new("Console")
+        .open(&mut open)
+        .default_width(480.0)
+        .show(&egui_context, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(240.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for line in &console.log {
+                        ui.monospace(line);
+                    }
+                });
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut console.input)
+                    .desired_width(f32::INFINITY)
+                    .lock_focus(true)
+                    .font(egui::TextStyle::Monospace),
+            );
+
+            if let Some(input) = input_before_open {
+                console.input = input;
+            }
+
+            let (enter, up, down, tab) = {
+                let input = ui.input();
+                (
+                    input.key_pressed(egui::Key::Enter),
+                    input.key_pressed(egui::Key::ArrowUp),
+                    input.key_pressed(egui::Key::ArrowDown),
+                    input.key_pressed(egui::Key::Tab),
+                )
+            };
+
+            if response.lost_focus() && enter {
+                let line = std::mem::take(&mut console.input);
+                if !line.trim().is_empty() {
+                    console.submit(line);
+                }
+            } else if up {
+                console.browse_history(-1);
+            } else if down {
+                console.browse_history(1);
+            } else if tab {
+                let input = console.input.clone();
+                let (completed, candidates) = complete(&commands, &input);
+                if candidates.len() > 1 {
+                    console.print(candidates.join("  "));
+                }
+                console.input = completed;
+            }
+
+            response.request_focus();
+        });
+
+    if !open {
+        console.open = false;
+    }
+}
+
+// Completes the command name to the longest prefix shared by every matching command.
+fn complete(commands: &ConsoleCommands, input: &str) -> (String, Vec<String>) {
+    if input.contains(' ') {
+        return (input.to_string(), vec![]);
+    }
+
+    let candidates: Vec<String> = commands
+        .names_starting_with(input)
+        .map(str::to_string)
+        .collect();
+
+    let Some(first) = candidates.first() else {
+        return (input.to_string(), candidates);
+    };
+
+    let mut prefix = first.clone();
+    for candidate in &candidates[1..] {
+        let shared = prefix
+            .chars()
+            .zip(candidate.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        prefix.truncate(shared);
+    }
+
+    if candidates.len() == 1 {
+        prefix.push(' ');
+    }
+
+    (prefix, candidates)
+}
+
+fn execute_console_commands(world: &mut EcsWorld) {
+    let pending = std::mem::take(&mut world.resource_mut::<Console>().pending);
+
+    for line in pending {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((name, args)) = words.split_first() else {
+            continue;
+        };
+
+        let command = world.resource::<ConsoleCommands>().get(name).cloned();
+        let output = match command {
+            Some(command) => (command.handler)(world, args),
+            None => Err(format!("Unknown command `{name}`, try `help`")),
+        };
+
+        let mut console = world.resource_mut::<Console>();
+        console.print(format!("> {line}"));
+        match output {
+            Ok(message) if message.is_empty() => {}
+            Ok(message) => console.print(message),
+            Err(message) => console.print(format!("Error: {message}")),
+        }
+    }
+}
+
+fn camera_position(world: &mut EcsWorld) -> Vec3 {
+    world
+        .query_filtered::<&Transform, With<CameraState>>()
+        .single(world)
+        .translation
+}
+
+// Coordinates prefixed with `~` are relative to `origin`.
+fn parse_coordinate(arg: &str, origin: f32) -> Result<f32, String> {
+    let (relative, number) = match arg.strip_prefix('~') {
+        Some(rest) => (true, rest),
+        None => (false, arg),
+    };
+
+    let value = if relative && number.is_empty() {
+        0.0
+    } else {
+        number
+            .parse::<f32>()
+            .map_err(|_| format!("`{arg}` is not a coordinate"))?
+    };
+
+    Ok(if relative { origin + value } else { value })
+}
+
+fn parse_position(args: &[&str], origin: Vec3) -> Result<Vec3, String> {
+    let [x, y, z] = args else {
+        return Err("expected three coordinates".to_string());
+    };
+
+    Ok(Vec3::new(
+        parse_coordinate(x, origin.x)?,
+        parse_coordinate(y, origin.y)?,
+        parse_coordinate(z, origin.z)?,
+    ))
+}
+
+fn parse_voxel_position(args: &[&str], origin: Vec3) -> Result<IVec3, String> {
+    Ok(parse_position(args, origin)?.round().as_ivec3())
+}
+
+fn parse_voxel_type(name: &str) -> Result<VoxelType, String> {
+    VoxelType::ALL
+        .into_iter()
+        .find(|voxel| format!("{voxel:?}").eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("unknown block `{name}`"))
+}
+
+fn set_voxels(world: &mut EcsWorld, voxel: VoxelType, positions: impl Iterator<Item = IVec3>) {
+    world.resource_scope(|world, mut voxel_world: Mut<World>| {
+        world.resource_scope(|world, mut simulation: Mut<FluidSimulation>| {
+            let mut dirty_chunks = world.resource_mut::<DirtyChunks>();
+            for position in positions {
+                voxel_world.set_voxel(voxel, position);
+                dirty_chunks.mark_voxel_dirty(position);
+                simulation.schedule(position);
+            }
+        });
+    });
+}
+
+fn help(world: &mut EcsWorld, _args: &[&str]) -> Result<String, String> {
+    let commands = world.resource::<ConsoleCommands>();
+    Ok(commands
+        .0
+        .values()
+        .map(|command| command.usage)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn teleport(world: &mut EcsWorld, args: &[&str]) -> Result<String, String> {
+    let origin = camera_position(world);
+    let position = parse_position(args, origin)?;
+
+    let mut query = world.query_filtered::<&mut Transform, With<CameraState>>();
+    query.single_mut(world).translation = position;
+
+    Ok(format!(
+        "Teleported to {:.1} {:.1} {:.1}",
+        position.x, position.y, position.z
+    ))
+}
+
+fn set_block(world: &mut EcsWorld, args: &[&str]) -> Result<String, String> {
+    let [x, y, z, block] = args else {
+        return Err("usage: setblock <x> <y> <z> <block>".to_string());
+    };
+
+    let origin = camera_position(world);
+    let position = parse_voxel_position(&[x, y, z], origin)?;
+    let voxel = parse_voxel_type(block)?;
+
+    set_voxels(world, voxel, std::iter::once(position));
+    Ok(String::new())
+}
+
+fn fill(world: &mut EcsWorld, args: &[&str]) -> Result<String, String> {
+    let [x1, y1, z1, x2, y2, z2, block] = args else {
+        return Err("usage: fill <x1> <y1> <z1> <x2> <y2> <z2> <block>".to_string());
+    };
+
+    let origin = camera_position(world);
+    let a = parse_voxel_position(&[x1, y1, z1], origin)?;
+    let b = parse_voxel_position(&[x2, y2, z2], origin)?;
+    let voxel = parse_voxel_type(block)?;
+
+    let min = a.min(b);
+    let max = a.max(b);
+    let size = max - min + IVec3::ONE;
+    let volume = size.x as i64 * size.y as i64 * size.z as i64;
+    if volume > MAX_FILL_VOLUME {
+        return Err(format!(
+            "{volume} blocks is more than the limit of {MAX_FILL_VOLUME}"
+        ));
+    }
+
+    let positions = (min.x..=max.x).flat_map(move |x| {
+        (min.y..=max.y).flat_map(move |y| (min.z..=max.z).map(move |z| IVec3::new(x, y, z)))
+    });
+    set_voxels(world, voxel, positions);
+
+    Ok(format!("Filled {volume} blocks"))
+}
+
+fn seed(world: &mut EcsWorld, _args: &[&str]) -> Result<String, String> {
+    Ok(format!("Seed: {}", world.resource::<World>().seed()))
+}
+
+fn view_distance(world: &mut EcsWorld, args: &[&str]) -> Result<String, String> {
+    let parse = |arg: &str, range: RangeInclusive<i32>| {
+        let distance = arg
+            .parse::<i32>()
+            .map_err(|_| format!("`{arg}` is not a valid distance"))?;
+        if !range.contains(&distance) {
+            return Err(format!(
+                "distance must be between {} and {}",
+                range.start(),
+                range.end()
+            ));
+        }
+        Ok(distance)
+    };
+
+    let mut settings = world.resource_mut::<Settings>();
+    match args {
+        [] => {}
+        [distance] => settings.view_distance = parse(distance, VIEW_DISTANCE_RANGE)?,
+        [distance, vertical] => {
+            let distance = parse(distance, VIEW_DISTANCE_RANGE)?;
+            settings.vertical_view_distance = parse(vertical, VERTICAL_VIEW_DISTANCE_RANGE)?;
+            settings.view_distance = distance;
+        }
+        _ => return Err("usage: viewdistance [distance] [vertical distance]".to_string()),
+    }
+
+    Ok(format!(
+        "View distance: {}, vertical: {}",
+        settings.view_distance, settings.vertical_view_distance
+    ))
+}
+
+fn regenerate(world: &mut EcsWorld, args: &[&str]) -> Result<String, String> {
+    let Some((&"chunk", args)) = args.split_first() else {
+        return Err("usage: regen chunk [x y z]".to_string());
+    };
+
+    let chunk_position = match args {
+        [] => World::world_to_chunk_position(camera_position(world).as_ivec3()),
+        _ => {
+            let parse = |arg: &str| {
+                arg.parse::<i32>()
+                    .map_err(|_| format!("`{arg}` is not a chunk coordinate"))
+            };
+            let [x, y, z] = args else {
+                return Err("expected three chunk coordinates".to_string());
+            };
+            IVec3::new(parse(x)?, parse(y)?, parse(z)?)
+        }
+    };
+
+    let mut voxel_world = world.resource_mut::<World>();
+    if !voxel_world.chunk_exists(chunk_position) {
+        return Err(format!("chunk {chunk_position} is not loaded"));
+    }
+
+    let seed = voxel_world.seed();
+    let chunk = Chunk::generate_at(chunk_position, seed).unwrap();
+    let border = chunk
+        .iter_voxels()
+        .filter(|position| {
+            position.min_element() == 0 || position.max_element() == CHUNK_SIZE_I32 - 1
+        })
+        .map(|position| chunk_position * CHUNK_SIZE_I32 + position)
+        .collect::<Vec<_>>();
+    voxel_world.set_chunk(chunk_position, chunk);
+
+    // Liquids on either side of the border may have to flow again.
+    let mut simulation = world.resource_mut::<FluidSimulation>();
+    for position in border {
+        simulation.schedule(position);
+    }
+
+    // Border voxels changed too, so the neighbours need new meshes as well.
+    let mut dirty_chunks = world.resource_mut::<DirtyChunks>();
+    dirty_chunks.mark_dirty(chunk_position);
+    for offset in [IVec3::X, IVec3::Y, IVec3::Z] {
+        dirty_chunks.mark_dirty(chunk_position + offset);
+        dirty_chunks.mark_dirty(chunk_position - offset);
+    }
+
+    Ok(format!("Regenerated chunk {chunk_position}"))
+}
+
+fn save(world: &mut EcsWorld, _args: &[&str]) -> Result<String, String> {
+    let directory = world.resource::<WorldSaveDirectory>().0.clone();
+    let voxel_world = world.resource::<World>();
+
+    storage::save_seed(&directory, voxel_world.seed()).map_err(|err| err.to_string())?;
+    for chunk in voxel_world.chunks().values() {
+        storage::save_chunk(&directory, chunk).map_err(|err| err.to_string())?;
+    }
+
+    Ok(format!(
+        "Saved {} chunks to {}",
+        voxel_world.chunks().len(),
+        directory.display()
+    ))
+}
+
+#[test]
+fn parses_relative_coordinates() {
+    assert_eq!(parse_coordinate("12.5", 3.0), Ok(12.5));
+    assert_eq!(parse_coordinate("~", 3.0), Ok(3.0));
+    assert_eq!(parse_coordinate("~-2", 3.0), Ok(1.0));
+    assert!(parse_coordinate("x", 3.0).is_err());
+}
+
+#[test]
+fn completes_shared_command_prefix() {
+    let mut commands = ConsoleCommands::default();
+    commands.register("seed", "seed", seed);
+    commands.register("setblock", "setblock", set_block);
+    commands.register("save", "save", save);
+
+    assert_eq!(complete(&commands, "se").0, "se");
+    assert_eq!(complete(&commands, "set").0, "setblock ");
+    assert_eq!(complete(&commands, "sa").0, "save ");
+    assert_eq!(complete(&commands, "x").1.len(), 0);
+}
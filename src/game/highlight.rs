@@ -0,0 +1,144 @@
+use bevy::{prelude::*, window::CursorGrabMode};
+use bevy_inspector_egui::bevy_egui::EguiContext;
+
+use crate::rendering::{box_outline_mesh, quad_mesh, OutlineMaterial};
+
+use super::{update_targeted_voxel, MiningProgress, TargetedVoxel};
+
+// Pushes the highlight slightly out of the voxel so it doesn't z-fight with the chunk mesh.
+const HIGHLIGHT_OFFSET: f32 = 0.005;
+const CROSSHAIR_SIZE: f32 = 8.0;
+
+#[derive(Component)]
+enum TargetHighlight {
+    Outline,
+    Face,
+}
+
+#[derive(Resource)]
+struct FaceHighlightMaterial(Handle<OutlineMaterial>);
+
+pub struct HighlightPlugin;
+impl Plugin for HighlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_highlight)
+            .add_system(update_highlight.after(update_targeted_voxel))
+            .add_system(crosshair_ui);
+    }
+}
+
+fn setup_highlight(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<OutlineMaterial>>,
+) {
+    commands.spawn((
+        TargetHighlight::Outline,
+        MaterialMeshBundle {
+            mesh: meshes.add(box_outline_mesh(Vec3::splat(0.5))),
+            material: materials.add(OutlineMaterial {
+                color: Color::rgba(0.0, 0.0, 0.0, 0.8),
+            }),
+            visibility: Visibility::INVISIBLE,
+            ..Default::default()
+        },
+    ));
+
+    let face_material = materials.add(OutlineMaterial {
+        color: Color::rgba(1.0, 1.0, 1.0, 0.2),
+    });
+    commands.insert_resource(FaceHighlightMaterial(face_material.clone()));
+
+    commands.spawn((
+        TargetHighlight::Face,
+        MaterialMeshBundle {
+            mesh: meshes.add(quad_mesh(1.0)),
+            material: face_material,
+            visibility: Visibility::INVISIBLE,
+            ..Default::default()
+        },
+    ));
+}
+
+fn update_highlight(
+    targeted_voxel: Res<TargetedVoxel>,
+    mining: Res<MiningProgress>,
+    face_material: Res<FaceHighlightMaterial>,
+    mut materials: ResMut<Assets<OutlineMaterial>>,
+    mut highlights: Query<(&TargetHighlight, &mut Transform, &mut Visibility)>,
+) {
+    let Some(hit) = targeted_voxel.0 else {
+        for (_, _, mut visibility) in &mut highlights {
+            visibility.is_visible = false;
+        }
+        return;
+    };
+
+    // Both highlights follow the box of the block's shape that was hit, not the whole voxel.
+    let center = hit.hit_box.center();
+    let size = hit.hit_box.max - hit.hit_box.min;
+    let normal = hit.face.normal().as_vec3();
+
+    for (highlight, mut transform, mut visibility) in &mut highlights {
+        visibility.is_visible = true;
+        match highlight {
+            TargetHighlight::Outline => {
+                transform.translation = center;
+                transform.scale = size + HIGHLIGHT_OFFSET * 2.0;
+            }
+            // The highlighted face is the one a placed block would be attached to.
+            TargetHighlight::Face => {
+                let rotation = Quat::from_rotation_arc(Vec3::Z, normal);
+                let face_size = (rotation.inverse() * size).abs();
+                transform.translation =
+                    center + normal * (size.dot(normal.abs()) * 0.5 + HIGHLIGHT_OFFSET);
+                transform.rotation = rotation;
+                transform.scale = Vec3::new(face_size.x, face_size.y, 1.0);
+            }
+        }
+    }
+
+    // Mining darkens the face as it progresses.
+    if mining.is_changed() || targeted_voxel.is_changed() {
+        if let Some(material) = materials.get_mut(&face_material.0) {
+            let progress = match mining.target {
+                Some(target) if target == hit.voxel_position => mining.progress,
+                _ => 0.0,
+            };
+            let shade = 1.0 - progress;
+            material.color = Color::rgba(shade, shade, shade, 0.2 + progress * 0.4);
+        }
+    }
+}
+
+fn crosshair_ui(windows: Res<Windows>, mut ctx: ResMut<EguiContext>) {
+    let grabbed = windows
+        .get_primary()
+        .is_some_and(|window| window.cursor_grab_mode() == CursorGrabMode::Confined);
+    if !grabbed {
+        return;
+    }
+
+    let egui_context = ctx.ctx_mut().clone();
+    let painter = egui_context.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("crosshair"),
+    ));
+
+    let center = egui_context.input().screen_rect().center();
+    let stroke = egui::Stroke::new(2.0, egui::Color32::WHITE);
+    painter.line_segment(
+        [
+            center - egui::vec2(CROSSHAIR_SIZE, 0.0),
+            center + egui::vec2(CROSSHAIR_SIZE, 0.0),
+        ],
+        stroke,
+    );
+    painter.line_segment(
+        [
+            center - egui::vec2(0.0, CROSSHAIR_SIZE),
+            center + egui::vec2(0.0, CROSSHAIR_SIZE),
+        ],
+        stroke,
+    );
+}
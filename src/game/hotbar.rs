@@ -0,0 +1,139 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+use bevy_inspector_egui::bevy_egui::EguiContext;
+
+use crate::data::VoxelType;
+
+use super::{update_targeted_voxel, Action, ActionState, TargetedVoxel};
+
+pub const HOTBAR_SIZE: usize = 9;
+
+#[derive(Resource, Debug, Clone)]
+pub struct Hotbar {
+    pub slots: [Option<VoxelType>; HOTBAR_SIZE],
+    pub selected: usize,
+}
+
+impl Default for Hotbar {
+    fn default() -> Self {
+        let mut slots = [None; HOTBAR_SIZE];
+        slots[0] = Some(VoxelType::Grass);
+        slots[1] = Some(VoxelType::Dirt);
+        slots[2] = Some(VoxelType::Stone);
+        slots[3] = Some(VoxelType::Water);
+        slots[4] = Some(VoxelType::Lava);
+        slots[5] = Some(VoxelType::Glass);
+        slots[6] = Some(VoxelType::Leaves);
+        slots[7] = Some(VoxelType::StoneSlab);
+        slots[8] = Some(VoxelType::StoneStairs);
+
+        Self { slots, selected: 0 }
+    }
+}
+
+impl Hotbar {
+    pub fn selected_voxel(&self) -> Option<VoxelType> {
+        self.slots[self.selected]
+    }
+
+    pub fn select(&mut self, slot: usize) {
+        if slot < HOTBAR_SIZE {
+            self.selected = slot;
+        }
+    }
+
+    pub fn cycle(&mut self, offset: i32) {
+        self.selected = (self.selected as i32 + offset).rem_euclid(HOTBAR_SIZE as i32) as usize;
+    }
+
+    // Selects the slot holding `voxel`, or puts it in the current slot if it's not in the hotbar.
+    pub fn pick(&mut self, voxel: VoxelType) {
+        match self.slots.iter().position(|slot| *slot == Some(voxel)) {
+            Some(slot) => self.selected = slot,
+            None => self.slots[self.selected] = Some(voxel),
+        }
+    }
+}
+
+pub struct HotbarPlugin;
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Hotbar>()
+            .add_system(select_hotbar_slot)
+            .add_system(pick_block.after(update_targeted_voxel))
+            .add_system(hotbar_ui);
+    }
+}
+
+fn select_hotbar_slot(
+    actions: Res<ActionState>,
+    mut scroll_events: EventReader<MouseWheel>,
+    mut hotbar: ResMut<Hotbar>,
+) {
+    for slot in 0..HOTBAR_SIZE {
+        if actions.just_pressed(Action::SelectHotbarSlot(slot as u8)) {
+            hotbar.select(slot);
+        }
+    }
+
+    if actions.just_pressed(Action::HotbarNext) {
+        hotbar.cycle(1);
+    }
+    if actions.just_pressed(Action::HotbarPrevious) {
+        hotbar.cycle(-1);
+    }
+
+    // Always drain the events so scrolling done while adjusting the speed isn't applied later.
+    let mut scroll = 0.0;
+    for ev in scroll_events.iter() {
+        scroll += match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            MouseScrollUnit::Pixel => ev.y / 100.0,
+        };
+    }
+
+    // Scrolling adjusts the fly speed instead while the modifier is held.
+    if scroll != 0.0 && !actions.pressed(Action::AdjustSpeed) {
+        hotbar.cycle(-scroll.signum() as i32);
+    }
+}
+
+fn pick_block(
+    actions: Res<ActionState>,
+    targeted_voxel: Res<TargetedVoxel>,
+    mut hotbar: ResMut<Hotbar>,
+) {
+    if !actions.just_pressed(Action::PickBlock) {
+        return;
+    }
+
+    if let Some(hit) = targeted_voxel.0 {
+        hotbar.pick(hit.voxel_type);
+    }
+}
+
+fn hotbar_ui(mut ctx: ResMut<EguiContext>, mut hotbar: ResMut<Hotbar>) {
+    let egui_context = ctx.ctx_mut().clone();
+
+    egui::Area::new("hotbar")
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -12.0])
+        .show(&egui_context, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for slot in 0..HOTBAR_SIZE {
+                        let label = match hotbar.slots[slot] {
+                            Some(voxel) => format!("{}\n{:?}", slot + 1, voxel),
+                            None => format!("{}\n-", slot + 1),
+                        };
+
+                        let selected = hotbar.selected == slot;
+                        if ui.selectable_label(selected, label).clicked() {
+                            hotbar.select(slot);
+                        }
+                    }
+                });
+            });
+        });
+}
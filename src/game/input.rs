@@ -0,0 +1,336 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bevy::{input::InputSystem, prelude::*};
+use bevy_inspector_egui::bevy_egui::EguiContext;
+use serde::{Deserialize, Serialize};
+
+use super::config::*;
+
+const BINDINGS_FILE: &str = "bindings.ron";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Run,
+    Jump,
+    RollLeft,
+    RollRight,
+    LookLeft,
+    LookRight,
+    LookUp,
+    LookDown,
+    ToggleMovementMode,
+    BreakBlock,
+    PlaceBlock,
+    PickBlock,
+    SelectHotbarSlot(u8),
+    HotbarNext,
+    HotbarPrevious,
+    AdjustSpeed,
+    GrabCursor,
+    ReleaseCursor,
+    ToggleWireframe,
+    ToggleChunkBorders,
+    ToggleConsole,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    GamepadButton(GamepadButtonType),
+    GamepadAxis(GamepadAxisType, AxisDirection),
+}
+
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputBindings {
+    pub gamepad_deadzone: f32,
+    pub bindings: BTreeMap<Action, Vec<InputBinding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use AxisDirection::*;
+        use GamepadAxisType::*;
+        use InputBinding::*;
+
+        let mut bindings = BTreeMap::from([
+            (
+                Action::MoveForward,
+                vec![Key(KeyCode::W), GamepadAxis(LeftStickY, Positive)],
+            ),
+            (
+                Action::MoveBackward,
+                vec![Key(KeyCode::S), GamepadAxis(LeftStickY, Negative)],
+            ),
+            (
+                Action::MoveLeft,
+                vec![Key(KeyCode::A), GamepadAxis(LeftStickX, Negative)],
+            ),
+            (
+                Action::MoveRight,
+                vec![Key(KeyCode::D), GamepadAxis(LeftStickX, Positive)],
+            ),
+            (
+                Action::MoveUp,
+                vec![Key(KeyCode::Space), GamepadButton(GamepadButtonType::South)],
+            ),
+            (
+                Action::MoveDown,
+                vec![
+                    Key(KeyCode::LControl),
+                    GamepadButton(GamepadButtonType::East),
+                ],
+            ),
+            (
+                Action::Run,
+                vec![
+                    Key(KeyCode::LShift),
+                    GamepadButton(GamepadButtonType::LeftThumb),
+                ],
+            ),
+            (
+                Action::Jump,
+                vec![Key(KeyCode::Space), GamepadButton(GamepadButtonType::South)],
+            ),
+            (
+                Action::RollLeft,
+                vec![
+                    Key(KeyCode::Q),
+                    GamepadButton(GamepadButtonType::LeftTrigger),
+                ],
+            ),
+            (
+                Action::RollRight,
+                vec![
+                    Key(KeyCode::E),
+                    GamepadButton(GamepadButtonType::RightTrigger),
+                ],
+            ),
+            (Action::LookLeft, vec![GamepadAxis(RightStickX, Negative)]),
+            (Action::LookRight, vec![GamepadAxis(RightStickX, Positive)]),
+            (Action::LookUp, vec![GamepadAxis(RightStickY, Positive)]),
+            (Action::LookDown, vec![GamepadAxis(RightStickY, Negative)]),
+            (
+                Action::ToggleMovementMode,
+                vec![Key(KeyCode::F), GamepadButton(GamepadButtonType::North)],
+            ),
+            (
+                Action::BreakBlock,
+                vec![
+                    Mouse(MouseButton::Left),
+                    GamepadButton(GamepadButtonType::RightTrigger2),
+                ],
+            ),
+            (
+                Action::PlaceBlock,
+                vec![
+                    Mouse(MouseButton::Right),
+                    GamepadButton(GamepadButtonType::LeftTrigger2),
+                ],
+            ),
+            (
+                Action::GrabCursor,
+                vec![
+                    Mouse(MouseButton::Left),
+                    GamepadButton(GamepadButtonType::Start),
+                ],
+            ),
+            (Action::PickBlock, vec![Mouse(MouseButton::Middle)]),
+            (
+                Action::HotbarNext,
+                vec![GamepadButton(GamepadButtonType::DPadRight)],
+            ),
+            (
+                Action::HotbarPrevious,
+                vec![GamepadButton(GamepadButtonType::DPadLeft)],
+            ),
+            (Action::AdjustSpeed, vec![Key(KeyCode::LAlt)]),
+            (Action::ReleaseCursor, vec![Key(KeyCode::Escape)]),
+            (Action::ToggleWireframe, vec![Key(KeyCode::F1)]),
+            (Action::ToggleChunkBorders, vec![Key(KeyCode::F2)]),
+            (Action::ToggleConsole, vec![Key(KeyCode::Grave)]),
+        ]);
+
+        let slot_keys = [
+            KeyCode::Key1,
+            KeyCode::Key2,
+            KeyCode::Key3,
+            KeyCode::Key4,
+            KeyCode::Key5,
+            KeyCode::Key6,
+            KeyCode::Key7,
+            KeyCode::Key8,
+            KeyCode::Key9,
+        ];
+        for (slot, key) in slot_keys.into_iter().enumerate() {
+            bindings.insert(Action::SelectHotbarSlot(slot as u8), vec![Key(key)]);
+        }
+
+        Self {
+            gamepad_deadzone: 0.15,
+            bindings,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+struct ActionData {
+    value: f32,
+    pressed: bool,
+    just_pressed: bool,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct ActionState {
+    actions: HashMap<Action, ActionData>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.actions.get(&action).is_some_and(|data| data.pressed)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.actions
+            .get(&action)
+            .is_some_and(|data| data.just_pressed)
+    }
+
+    // Analog strength in `0.0..=1.0`; digital bindings are either 0 or 1.
+    pub fn value(&self, action: Action) -> f32 {
+        self.actions.get(&action).map_or(0.0, |data| data.value)
+    }
+
+    pub fn axis(&self, positive: Action, negative: Action) -> f32 {
+        self.value(positive) - self.value(negative)
+    }
+}
+
+impl InputBindings {
+    // Binding files written by older versions don't know about newer actions.
+    pub fn with_missing_defaults(mut self) -> Self {
+        for (action, bindings) in InputBindings::default().bindings {
+            self.bindings.entry(action).or_insert(bindings);
+        }
+        self
+    }
+}
+
+pub struct InputBindingsPlugin;
+impl Plugin for InputBindingsPlugin {
+    fn build(&self, app: &mut App) {
+        let bindings = load_config_or_default::<InputBindings>(&config_path(BINDINGS_FILE))
+            .with_missing_defaults();
+
+        app.insert_resource(bindings)
+            .init_resource::<ActionState>()
+            .add_system_to_stage(CoreStage::PreUpdate, update_action_state.after(InputSystem))
+            .add_system(save_bindings);
+    }
+}
+
+fn binding_value(
+    binding: InputBinding,
+    deadzone: f32,
+    keys: &Input<KeyCode>,
+    mouse_buttons: &Input<MouseButton>,
+    gamepads: &Gamepads,
+    gamepad_buttons: &Input<GamepadButton>,
+    gamepad_axes: &Axis<GamepadAxis>,
+) -> f32 {
+    match binding {
+        InputBinding::Key(key) => keys.pressed(key) as u8 as f32,
+        InputBinding::Mouse(button) => mouse_buttons.pressed(button) as u8 as f32,
+        InputBinding::GamepadButton(button_type) => gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.pressed(GamepadButton::new(gamepad, button_type)))
+            as u8 as f32,
+        InputBinding::GamepadAxis(axis_type, direction) => gamepads
+            .iter()
+            .filter_map(|gamepad| gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)))
+            .map(|value| match direction {
+                AxisDirection::Positive => value,
+                AxisDirection::Negative => -value,
+            })
+            .filter(|value| *value > deadzone)
+            .fold(0.0, f32::max),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_action_state(
+    mut egui: ResMut<EguiContext>,
+    bindings: Res<InputBindings>,
+    mut action_state: ResMut<ActionState>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+) {
+    // Keys typed into a text field (e.g. the console) shouldn't also move the player around.
+    let typing = egui.ctx_mut().wants_keyboard_input();
+
+    for (action, action_bindings) in &bindings.bindings {
+        let value = action_bindings
+            .iter()
+            .filter(|binding| {
+                !typing
+                    || *action == Action::ToggleConsole
+                    || !matches!(binding, InputBinding::Key(_))
+            })
+            .map(|binding| {
+                binding_value(
+                    *binding,
+                    bindings.gamepad_deadzone,
+                    &keys,
+                    &mouse_buttons,
+                    &gamepads,
+                    &gamepad_buttons,
+                    &gamepad_axes,
+                )
+            })
+            .fold(0.0, f32::max)
+            .min(1.0);
+
+        let data = action_state.actions.entry(*action).or_default();
+        let pressed = value > 0.0;
+
+        data.just_pressed = pressed && !data.pressed;
+        data.pressed = pressed;
+        data.value = value;
+    }
+}
+
+fn save_bindings(bindings: Res<InputBindings>) {
+    if !bindings.is_changed() || bindings.is_added() {
+        return;
+    }
+
+    if let Err(err) = save_config(&config_path(BINDINGS_FILE), &*bindings) {
+        warn!("Failed to save input bindings: {err}");
+    }
+}
+
+#[test]
+fn bindings_round_trip_through_ron() {
+    let bindings = InputBindings::default();
+    let contents =
+        ron::ser::to_string_pretty(&bindings, ron::ser::PrettyConfig::default()).unwrap();
+    let parsed: InputBindings = ron::from_str(&contents).unwrap();
+
+    assert_eq!(parsed.bindings, bindings.bindings);
+}
@@ -0,0 +1,216 @@
+use bevy::{prelude::*, window::CursorGrabMode};
+
+use crate::{
+    data::{block_state::BlockState, VoxelType},
+    world::{Aabb, FluidSimulation, RaytraceResult, World},
+};
+
+use super::{Action, ActionState, CameraState, DirtyChunks, Hotbar, PlayerController, Settings};
+
+const REACH: f32 = 30.0;
+
+#[derive(Resource, Debug, Default)]
+pub struct TargetedVoxel(pub Option<RaytraceResult>);
+
+#[derive(Resource, Debug, Default)]
+pub struct MiningProgress {
+    pub target: Option<IVec3>,
+    pub progress: f32,
+}
+
+#[derive(Resource, Debug, Default)]
+struct InteractionCooldowns {
+    break_block: f32,
+    place_block: f32,
+}
+
+pub struct InteractionPlugin;
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TargetedVoxel>()
+            .init_resource::<MiningProgress>()
+            .init_resource::<InteractionCooldowns>()
+            .add_system(update_targeted_voxel)
+            .add_system(break_voxels.after(update_targeted_voxel))
+            .add_system(place_voxels.after(break_voxels));
+    }
+}
+
+pub fn update_targeted_voxel(
+    windows: Res<Windows>,
+    world: Res<World>,
+    mut targeted_voxel: ResMut<TargetedVoxel>,
+    query: Query<&Transform, With<CameraState>>,
+) {
+    let grabbed = windows
+        .get_primary()
+        .is_some_and(|window| window.cursor_grab_mode() == CursorGrabMode::Confined);
+
+    let target = if grabbed {
+        let transform = query.single();
+        world.raytrace(transform.translation, transform.forward(), REACH)
+    } else {
+        None
+    };
+
+    if targeted_voxel.0 != target {
+        targeted_voxel.0 = target;
+    }
+}
+
+// Returns true when a held action should fire this frame: immediately on press, then once every
+// `interval` seconds while held.
+fn tick_repeat(
+    cooldown: &mut f32,
+    just_pressed: bool,
+    pressed: bool,
+    interval: f32,
+    dt: f32,
+) -> bool {
+    if just_pressed {
+        *cooldown = interval;
+        return true;
+    }
+
+    if !pressed {
+        return false;
+    }
+
+    *cooldown -= dt;
+    if *cooldown > 0.0 {
+        return false;
+    }
+
+    *cooldown += interval;
+    true
+}
+
+fn set_voxel(
+    world: &mut World,
+    dirty_chunks: &mut DirtyChunks,
+    simulation: &mut FluidSimulation,
+    voxel: VoxelType,
+    state: BlockState,
+    position: IVec3,
+) {
+    world.set_voxel_with_state(voxel, state, position);
+    dirty_chunks.mark_voxel_dirty(position);
+    simulation.schedule(position);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn break_voxels(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    actions: Res<ActionState>,
+    targeted_voxel: Res<TargetedVoxel>,
+    mut mining: ResMut<MiningProgress>,
+    mut cooldowns: ResMut<InteractionCooldowns>,
+    mut dirty_chunks: ResMut<DirtyChunks>,
+    mut simulation: ResMut<FluidSimulation>,
+    mut world: ResMut<World>,
+) {
+    let pressed = actions.pressed(Action::BreakBlock);
+    let Some(hit) = targeted_voxel.0.filter(|_| pressed) else {
+        if mining.target.is_some() {
+            *mining = MiningProgress::default();
+        }
+        return;
+    };
+
+    if !settings.survival_mining {
+        let should_break = tick_repeat(
+            &mut cooldowns.break_block,
+            actions.just_pressed(Action::BreakBlock),
+            pressed,
+            settings.block_repeat_interval,
+            time.delta_seconds(),
+        );
+
+        if should_break {
+            set_voxel(
+                &mut world,
+                &mut dirty_chunks,
+                &mut simulation,
+                VoxelType::Air,
+                BlockState::DEFAULT,
+                hit.voxel_position,
+            );
+        }
+        return;
+    }
+
+    if mining.target != Some(hit.voxel_position) {
+        mining.target = Some(hit.voxel_position);
+        mining.progress = 0.0;
+    }
+
+    let hardness = hit.voxel_type.hardness();
+    mining.progress += if hardness > 0.0 {
+        time.delta_seconds() / hardness
+    } else {
+        1.0
+    };
+
+    if mining.progress >= 1.0 {
+        set_voxel(
+            &mut world,
+            &mut dirty_chunks,
+            &mut simulation,
+            VoxelType::Air,
+            BlockState::DEFAULT,
+            hit.voxel_position,
+        );
+        *mining = MiningProgress::default();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn place_voxels(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    actions: Res<ActionState>,
+    targeted_voxel: Res<TargetedVoxel>,
+    hotbar: Res<Hotbar>,
+    mut cooldowns: ResMut<InteractionCooldowns>,
+    mut dirty_chunks: ResMut<DirtyChunks>,
+    mut simulation: ResMut<FluidSimulation>,
+    mut world: ResMut<World>,
+    player: Query<(&Transform, &PlayerController)>,
+) {
+    let should_place = tick_repeat(
+        &mut cooldowns.place_block,
+        actions.just_pressed(Action::PlaceBlock),
+        actions.pressed(Action::PlaceBlock),
+        settings.block_repeat_interval,
+        time.delta_seconds(),
+    );
+
+    let (true, Some(hit), Some(voxel_type)) =
+        (should_place, targeted_voxel.0, hotbar.selected_voxel())
+    else {
+        return;
+    };
+
+    let voxel_position = hit.voxel_position + hit.face.normal();
+    if !world.get_voxel(voxel_position).is_replaceable() {
+        return;
+    }
+
+    let (transform, controller) = player.single();
+    if controller
+        .aabb(transform.translation)
+        .intersects(&Aabb::voxel(voxel_position))
+    {
+        return;
+    }
+
+    set_voxel(
+        &mut world,
+        &mut dirty_chunks,
+        &mut simulation,
+        voxel_type,
+        voxel_type.placement_state(hit.face, transform.forward()),
+        voxel_position,
+    );
+}
@@ -1,5 +1,22 @@
 mod camera;
+mod config;
+mod console;
+mod highlight;
+mod hotbar;
+mod input;
+mod interaction;
+mod player;
+mod settings;
+mod sky;
 mod world;
 
 pub use camera::*;
+pub use console::*;
+pub use highlight::*;
+pub use hotbar::*;
+pub use input::*;
+pub use interaction::*;
+pub use player::*;
+pub use settings::*;
+pub use sky::*;
 pub use world::*;
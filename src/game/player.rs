@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+
+use crate::world::{Aabb, World};
+
+use super::{Action, ActionState, CameraState};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum MovementMode {
+    #[default]
+    Fly,
+    Walk,
+}
+
+#[derive(Component)]
+pub struct PlayerController {
+    pub mode: MovementMode,
+    pub half_extents: Vec3,
+    pub eye_height: f32,
+    pub walk_speed: f32,
+    pub run_multiplier: f32,
+    pub jump_speed: f32,
+    pub gravity: f32,
+    pub step_height: f32,
+    pub velocity: Vec3,
+    pub on_ground: bool,
+}
+
+impl Default for PlayerController {
+    fn default() -> Self {
+        Self {
+            mode: MovementMode::default(),
+            half_extents: Vec3::new(0.3, 0.9, 0.3),
+            eye_height: 1.62,
+            walk_speed: 4.3,
+            run_multiplier: 1.5,
+            jump_speed: 8.5,
+            gravity: 28.0,
+            step_height: 1.0,
+            velocity: Vec3::ZERO,
+            on_ground: false,
+        }
+    }
+}
+
+impl PlayerController {
+    // The camera sits at eye height, the body box is measured from the feet.
+    pub fn aabb(&self, eye_position: Vec3) -> Aabb {
+        let feet = eye_position - Vec3::Y * self.eye_height;
+        Aabb::from_center_half_extents(feet + Vec3::Y * self.half_extents.y, self.half_extents)
+    }
+}
+
+pub struct PlayerPlugin;
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_movement_mode)
+            .add_system(move_player.after(toggle_movement_mode));
+    }
+}
+
+fn toggle_movement_mode(actions: Res<ActionState>, mut query: Query<&mut PlayerController>) {
+    if !actions.just_pressed(Action::ToggleMovementMode) {
+        return;
+    }
+
+    for mut controller in &mut query {
+        controller.mode = match controller.mode {
+            MovementMode::Fly => MovementMode::Walk,
+            MovementMode::Walk => MovementMode::Fly,
+        };
+        controller.velocity = Vec3::ZERO;
+        controller.on_ground = false;
+    }
+}
+
+fn move_player(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    world: Res<World>,
+    mut query: Query<(&mut Transform, &mut CameraState, &mut PlayerController)>,
+) {
+    let (mut transform, mut state, mut controller) = query.single_mut();
+    if controller.mode != MovementMode::Walk {
+        return;
+    }
+
+    // Don't fall into the void while the chunk below us is still generating.
+    let chunk_position = World::world_to_chunk_position(transform.translation.as_ivec3());
+    if !world.chunk_exists(chunk_position) {
+        return;
+    }
+
+    let forward = (transform.forward() * Vec3::new(1.0, 0.0, 1.0)).normalize_or_zero();
+    let right = forward.cross(Vec3::Y);
+
+    let direction = forward * actions.axis(Action::MoveForward, Action::MoveBackward)
+        + right * actions.axis(Action::MoveRight, Action::MoveLeft);
+
+    let is_running = actions.pressed(Action::Run);
+    let speed = controller.walk_speed
+        * if is_running {
+            controller.run_multiplier
+        } else {
+            1.0
+        };
+
+    let horizontal = direction.clamp_length_max(1.0) * speed;
+    let mut velocity = Vec3::new(horizontal.x, controller.velocity.y, horizontal.z);
+
+    if controller.on_ground && actions.just_pressed(Action::Jump) {
+        velocity.y = controller.jump_speed;
+    }
+    velocity.y -= controller.gravity * time.delta_seconds();
+
+    let step_height = if controller.on_ground {
+        controller.step_height
+    } else {
+        0.0
+    };
+
+    let aabb = controller.aabb(transform.translation);
+    let result = world.move_aabb(&aabb, velocity * time.delta_seconds(), step_height);
+
+    if result.collided.y {
+        velocity.y = 0.0;
+    }
+
+    controller.velocity = velocity;
+    controller.on_ground = result.on_ground;
+    state.velocity = velocity;
+
+    transform.translation += result.offset;
+}
@@ -0,0 +1,230 @@
+use std::ops::RangeInclusive;
+
+use bevy::{prelude::*, window::PresentMode};
+use bevy_inspector_egui::bevy_egui::EguiContext;
+use serde::{Deserialize, Serialize};
+
+use super::{config::*, CameraState, ChunkComponent, ChunkLoader, DirtyChunks};
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+pub const VIEW_DISTANCE_RANGE: RangeInclusive<i32> = 2..=32;
+pub const VERTICAL_VIEW_DISTANCE_RANGE: RangeInclusive<i32> = 1..=16;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FogMode {
+    Off,
+    Linear,
+    Exponential,
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub view_distance: i32,
+    pub vertical_view_distance: i32,
+    pub unload_distance_margin: i32,
+    pub chunk_unload_delay: f32,
+    pub fov: f32,
+    pub mouse_sensitivity: f32,
+    pub fly_speed: f32,
+    pub vsync: bool,
+    pub block_repeat_interval: f32,
+    pub survival_mining: bool,
+    pub chunk_spawn_budget: usize,
+    pub chunk_generation_budget: usize,
+    pub chunk_meshing_budget: usize,
+    pub biome_blend_radius: i32,
+    pub fog_mode: FogMode,
+    // Where linear fog starts, as a fraction of the view distance.
+    pub fog_start: f32,
+    // Exponential fog density, relative to the view distance.
+    pub fog_density: f32,
+    // Real time seconds per in-game day.
+    pub day_length: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            view_distance: 8,
+            vertical_view_distance: 4,
+            unload_distance_margin: 2,
+            chunk_unload_delay: 5.0,
+            fov: 70.0,
+            mouse_sensitivity: 70.0,
+            fly_speed: 120.0,
+            vsync: false,
+            block_repeat_interval: 0.25,
+            survival_mining: false,
+            chunk_spawn_budget: 64,
+            chunk_generation_budget: 32,
+            chunk_meshing_budget: 16,
+            biome_blend_radius: 2,
+            fog_mode: FogMode::Linear,
+            fog_start: 0.6,
+            fog_density: 3.0,
+            day_length: 1200.0,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        load_config_or_default(&config_path(SETTINGS_FILE))
+    }
+
+    pub const fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .add_system(settings_ui)
+            .add_system(apply_settings.after(settings_ui))
+            .add_system(save_settings.after(settings_ui));
+    }
+}
+
+fn settings_ui(mut ctx: ResMut<EguiContext>, mut settings: ResMut<Settings>) {
+    let egui_context = ctx.ctx_mut().clone();
+    let mut edited = settings.clone();
+
+    egui::Window::new("Settings").show(&egui_context, |ui| {
+        ui.add(
+            egui::Slider::new(&mut edited.view_distance, VIEW_DISTANCE_RANGE).text("View distance"),
+        );
+        ui.add(
+            egui::Slider::new(
+                &mut edited.vertical_view_distance,
+                VERTICAL_VIEW_DISTANCE_RANGE,
+            )
+            .text("Vertical view distance"),
+        );
+        ui.add(
+            egui::Slider::new(&mut edited.unload_distance_margin, 0..=8)
+                .text("Unload distance margin"),
+        );
+        ui.add(
+            egui::Slider::new(&mut edited.chunk_unload_delay, 0.0..=30.0)
+                .text("Chunk unload delay"),
+        );
+        ui.add(egui::Slider::new(&mut edited.fov, 30.0..=120.0).text("FOV"));
+        ui.add(egui::Slider::new(&mut edited.mouse_sensitivity, 1.0..=300.0).text("Sensitivity"));
+        ui.add(
+            egui::Slider::new(&mut edited.fly_speed, 2.0..=2000.0)
+                .logarithmic(true)
+                .text("Fly speed"),
+        );
+        ui.checkbox(&mut edited.vsync, "VSync");
+        ui.add(
+            egui::Slider::new(&mut edited.block_repeat_interval, 0.05..=1.0)
+                .text("Block repeat interval"),
+        );
+        ui.checkbox(&mut edited.survival_mining, "Survival mining");
+        ui.add(
+            egui::Slider::new(&mut edited.chunk_spawn_budget, 1..=512)
+                .text("Chunk spawns per frame"),
+        );
+        ui.add(
+            egui::Slider::new(&mut edited.chunk_generation_budget, 1..=256)
+                .text("Chunk generations per frame"),
+        );
+        ui.add(
+            egui::Slider::new(&mut edited.chunk_meshing_budget, 1..=256)
+                .text("Chunk meshes per frame"),
+        );
+        ui.add(egui::Slider::new(&mut edited.biome_blend_radius, 0..=8).text("Biome blend radius"));
+        ui.horizontal(|ui| {
+            ui.label("Fog");
+            ui.radio_value(&mut edited.fog_mode, FogMode::Off, "Off");
+            ui.radio_value(&mut edited.fog_mode, FogMode::Linear, "Linear");
+            ui.radio_value(&mut edited.fog_mode, FogMode::Exponential, "Exponential");
+        });
+        ui.add(egui::Slider::new(&mut edited.fog_start, 0.0..=1.0).text("Fog start"));
+        ui.add(egui::Slider::new(&mut edited.fog_density, 0.5..=10.0).text("Fog density"));
+        ui.add(
+            egui::Slider::new(&mut edited.day_length, 60.0..=7200.0)
+                .logarithmic(true)
+                .text("Day length"),
+        );
+    });
+
+    if edited != *settings {
+        *settings = edited;
+    }
+}
+
+// Only fields that actually changed are applied, so e.g. tweaking the FOV doesn't reset a fly
+// speed adjusted with the scroll wheel.
+fn apply_settings(
+    settings: Res<Settings>,
+    mut previous: Local<Option<Settings>>,
+    mut windows: ResMut<Windows>,
+    mut camera: Query<(&mut Projection, &mut CameraState, &mut ChunkLoader)>,
+    mut dirty_chunks: ResMut<DirtyChunks>,
+    chunks: Query<&ChunkComponent>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let first_run = previous.is_none();
+    let old = previous.take().unwrap_or_else(|| settings.clone());
+    let (mut projection, mut state, mut loader) = camera.single_mut();
+
+    if first_run || old.fov != settings.fov {
+        state.fov = settings.fov;
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = settings.fov.to_radians();
+        }
+    }
+
+    if first_run || old.mouse_sensitivity != settings.mouse_sensitivity {
+        state.sensibility = settings.mouse_sensitivity;
+    }
+
+    if first_run || old.fly_speed != settings.fly_speed {
+        state.speed = settings.fly_speed.clamp(state.min_speed, state.max_speed);
+    }
+
+    if first_run
+        || old.view_distance != settings.view_distance
+        || old.vertical_view_distance != settings.vertical_view_distance
+    {
+        loader.radius = settings.view_distance;
+        loader.vertical_radius = settings.vertical_view_distance;
+    }
+
+    // Biome tints are baked into chunk meshes.
+    if !first_run && old.biome_blend_radius != settings.biome_blend_radius {
+        for chunk in &chunks {
+            dirty_chunks.mark_dirty(chunk.0);
+        }
+    }
+
+    if first_run || old.vsync != settings.vsync {
+        if let Some(window) = windows.get_primary_mut() {
+            window.set_present_mode(settings.present_mode());
+        }
+    }
+
+    *previous = Some(settings.clone());
+}
+
+fn save_settings(settings: Res<Settings>) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+
+    if let Err(err) = save_config(&config_path(SETTINGS_FILE), &*settings) {
+        warn!("Failed to save settings: {err}");
+    }
+}
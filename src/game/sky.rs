@@ -0,0 +1,198 @@
+use std::f32::consts::TAU;
+
+use bevy::{ecs::world::World as EcsWorld, prelude::*};
+
+use crate::{
+    data::constants::*,
+    rendering::{ChunkFog, ChunkMaterial},
+};
+
+use super::{ConsoleAppExt, FogMode, Settings};
+
+const DAY_SKY_COLOR: Color = Color::rgb(0.55, 0.74, 0.95);
+const NIGHT_SKY_COLOR: Color = Color::rgb(0.02, 0.03, 0.08);
+const SUNSET_SKY_COLOR: Color = Color::rgb(0.95, 0.5, 0.3);
+
+// Sky light never goes fully dark, so the terrain stays readable at night.
+const MIN_SKY_BRIGHTNESS: f32 = 0.15;
+// Daylight changes in steps, so the clear colour and chunk materials are only updated a few
+// hundred times per day instead of every frame.
+const DAYLIGHT_STEPS: f32 = 256.0;
+
+const NAMED_TIMES: [(&str, f32); 4] = [
+    ("sunrise", 0.25),
+    ("day", 0.3),
+    ("noon", 0.5),
+    ("night", 0.8),
+];
+
+// Time of day, from 0.0 at midnight to 0.5 at noon.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct WorldTime {
+    pub time_of_day: f32,
+    pub frozen: bool,
+}
+
+impl Default for WorldTime {
+    fn default() -> Self {
+        Self {
+            time_of_day: 0.3,
+            frozen: false,
+        }
+    }
+}
+
+impl WorldTime {
+    // Height of the sun, from -1.0 at midnight to 1.0 at noon.
+    pub fn sun_height(&self) -> f32 {
+        -(self.time_of_day * TAU).cos()
+    }
+
+    pub fn sky_brightness(&self) -> f32 {
+        let daylight = quantize(smoothstep(-0.2, 0.3, self.sun_height()));
+        MIN_SKY_BRIGHTNESS + (1.0 - MIN_SKY_BRIGHTNESS) * daylight
+    }
+
+    pub fn sky_color(&self) -> Color {
+        let daylight = quantize(smoothstep(-0.2, 0.3, self.sun_height()));
+        let sunset = quantize(1.0 - (self.sun_height().abs() / 0.25).min(1.0));
+
+        let night = Vec3::from_slice(&NIGHT_SKY_COLOR.as_rgba_f32());
+        let day = Vec3::from_slice(&DAY_SKY_COLOR.as_rgba_f32());
+        let sunset_color = Vec3::from_slice(&SUNSET_SKY_COLOR.as_rgba_f32());
+        let color = night.lerp(day, daylight).lerp(sunset_color, sunset * 0.6);
+
+        Color::rgb(color.x, color.y, color.z)
+    }
+}
+
+fn quantize(x: f32) -> f32 {
+    (x * DAYLIGHT_STEPS).round() / DAYLIGHT_STEPS
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+pub struct SkyPlugin;
+impl Plugin for SkyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(DAY_SKY_COLOR))
+            .init_resource::<WorldTime>()
+            .add_console_command("time", "time [set <time>|freeze|unfreeze]", time)
+            .add_system(advance_time)
+            .add_system(update_sky_color.after(advance_time))
+            .add_system(update_chunk_materials.after(update_sky_color));
+    }
+}
+
+fn advance_time(time: Res<Time>, settings: Res<Settings>, mut world_time: ResMut<WorldTime>) {
+    if world_time.frozen {
+        return;
+    }
+
+    let days = time.delta_seconds() / settings.day_length.max(1.0);
+    world_time.time_of_day = (world_time.time_of_day + days).rem_euclid(1.0);
+}
+
+fn update_sky_color(world_time: Res<WorldTime>, mut clear_color: ResMut<ClearColor>) {
+    if !world_time.is_changed() {
+        return;
+    }
+
+    let sky_color = world_time.sky_color();
+    if clear_color.0 != sky_color {
+        clear_color.0 = sky_color;
+    }
+}
+
+// Fog fades chunks into the clear colour, so the edge of the view distance isn't visible, and
+// sky light follows the time of day.
+fn update_chunk_materials(
+    settings: Res<Settings>,
+    clear_color: Res<ClearColor>,
+    world_time: Res<WorldTime>,
+    mut materials: ResMut<Assets<ChunkMaterial>>,
+) {
+    if !settings.is_changed() && !clear_color.is_changed() && !world_time.is_changed() {
+        return;
+    }
+
+    // Chunks are loaded within a sphere, fog should be opaque a bit before the last ones. At least
+    // one chunk of fog keeps the shader from dividing by zero at tiny view distances.
+    let end = ((settings.view_distance - 1).max(1) * CHUNK_SIZE_I32) as f32;
+    let start = (end * settings.fog_start.clamp(0.0, 1.0)).min(end - 1.0);
+    let fog = match settings.fog_mode {
+        FogMode::Off => ChunkFog::default(),
+        FogMode::Linear => ChunkFog::linear(clear_color.0, start, end),
+        FogMode::Exponential => ChunkFog::exponential(clear_color.0, settings.fog_density / end),
+    };
+    let sky_brightness = world_time.sky_brightness();
+
+    // Changing a material prepares its bind group again, so leave them alone when nothing did.
+    if materials
+        .iter()
+        .all(|(_, material)| material.fog == fog && material.sky_brightness == sky_brightness)
+    {
+        return;
+    }
+
+    for (_, material) in materials.iter_mut() {
+        material.fog = fog;
+        material.sky_brightness = sky_brightness;
+    }
+}
+
+fn parse_time(arg: &str) -> Result<f32, String> {
+    if let Some((_, time)) = NAMED_TIMES.iter().find(|(name, _)| *name == arg) {
+        return Ok(*time);
+    }
+
+    arg.parse::<f32>()
+        .ok()
+        .filter(|time| (0.0..=1.0).contains(time))
+        .ok_or_else(|| format!("`{arg}` is not a time between 0.0 and 1.0"))
+}
+
+fn time(world: &mut EcsWorld, args: &[&str]) -> Result<String, String> {
+    let mut world_time = world.resource_mut::<WorldTime>();
+    match args {
+        [] => {}
+        ["set", time] => world_time.time_of_day = parse_time(time)?.rem_euclid(1.0),
+        ["freeze"] => world_time.frozen = true,
+        ["unfreeze"] => world_time.frozen = false,
+        _ => return Err("usage: time [set <time>|freeze|unfreeze]".to_string()),
+    }
+
+    Ok(format!(
+        "Time: {:.3}{}",
+        world_time.time_of_day,
+        if world_time.frozen { " (frozen)" } else { "" }
+    ))
+}
+
+#[test]
+fn sky_is_brightest_at_noon() {
+    let at = |time_of_day| WorldTime {
+        time_of_day,
+        frozen: false,
+    };
+
+    assert_eq!(at(0.5).sky_brightness(), 1.0);
+    assert_eq!(at(0.0).sky_brightness(), MIN_SKY_BRIGHTNESS);
+    assert!(at(0.25).sky_brightness() > at(0.2).sky_brightness());
+}
+
+#[test]
+fn parses_named_and_numeric_times() {
+    assert_eq!(parse_time("noon"), Ok(0.5));
+    assert_eq!(parse_time("0.75"), Ok(0.75));
+    assert!(parse_time("1.5").is_err());
+    assert!(parse_time("dusk").is_err());
+
+    let mut world = EcsWorld::new();
+    world.init_resource::<WorldTime>();
+    time(&mut world, &["set", "1.0"]).unwrap();
+    assert_eq!(world.resource::<WorldTime>().time_of_day, 0.0);
+}
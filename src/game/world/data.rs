@@ -1,8 +1,16 @@
-use std::collections::{hash_map::Keys, HashMap, HashSet};
+use std::{
+    collections::{hash_map::Keys, HashMap, HashSet},
+    path::PathBuf,
+    time::Duration,
+};
 
 use bevy::{prelude::*, tasks::Task};
 
-use crate::world::Chunk;
+use crate::{
+    data::constants::*,
+    rendering::ChunkMaterial,
+    world::{Chunk, World},
+};
 
 #[derive(StageLabel, Hash, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ChunkLoadingStage;
@@ -18,11 +26,85 @@ pub struct ChunkMeshingStage;
 #[derive(Component, Default)]
 pub struct ChunkComponent(pub IVec3);
 
+// Materials shared by every chunk, one per render layer, so global uniforms like the fog only
+// have to be updated in a few places.
+#[derive(Resource)]
+pub struct ChunkMaterials {
+    pub opaque: Handle<ChunkMaterial>,
+    pub cutout: Handle<ChunkMaterial>,
+    pub translucent: Handle<ChunkMaterial>,
+}
+
+// Keeps the chunks around the entity loaded. The loaded area is the union of all loaders.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChunkLoader {
+    pub radius: i32,
+    pub vertical_radius: i32,
+}
+
+// Chunks are only meshed once every loaded neighbour has been generated, so border faces come
+// out right the first time.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChunkState {
+    Queued,
+    Generating,
+    Generated,
+    NeighboursReady,
+    Meshing,
+    Meshed,
+}
+
+impl ChunkState {
+    pub fn is_generated(&self) -> bool {
+        *self >= ChunkState::Generated
+    }
+
+    pub fn can_mesh(&self) -> bool {
+        *self >= ChunkState::NeighboursReady
+    }
+
+    pub fn is_pending(&self) -> bool {
+        *self != ChunkState::Meshed
+    }
+}
+
+// Child entities holding the chunk's cutout and translucent meshes. The chunk entity itself
+// holds the opaque mesh.
+#[derive(Component, Debug, Copy, Clone)]
+pub struct ChunkLayerEntities {
+    pub cutout: Entity,
+    pub translucent: Entity,
+}
+
 #[derive(Component)]
-pub struct TerrainGenerationTask(pub Task<Chunk>);
+pub struct TerrainGenerationTask(pub Task<(Chunk, Duration)>);
 
 #[derive(Component)]
-pub struct ChunkMeshingTask(pub Task<Mesh>);
+pub struct ChunkMeshingTask(pub Task<ChunkMeshingResult>);
+
+pub struct ChunkMeshingResult {
+    pub mesh: Mesh,
+    pub cutout_mesh: Mesh,
+    pub translucent_mesh: Mesh,
+    pub info: ChunkMeshInfo,
+    pub duration: Duration,
+}
+
+#[derive(Component, Debug, Copy, Clone, Default)]
+pub struct ChunkMeshInfo {
+    pub vertex_count: usize,
+    pub triangle_count: usize,
+}
+
+// Chunks saved in this directory are loaded instead of being generated.
+#[derive(Resource, Clone)]
+pub struct WorldSaveDirectory(pub PathBuf);
+
+impl Default for WorldSaveDirectory {
+    fn default() -> Self {
+        Self(PathBuf::from("world"))
+    }
+}
 
 #[derive(Resource, Default)]
 pub struct ChunkCommandQueue {
@@ -30,32 +112,75 @@ pub struct ChunkCommandQueue {
     pub destroy: Vec<IVec3>,
 }
 
+// Loaded chunks outside of the unload radius, with the time at which they get destroyed.
 #[derive(Resource, Default)]
-pub struct DirtyChunks(HashSet<IVec3>);
+pub struct ChunkUnloadQueue(HashMap<IVec3, f32>);
 
-impl DirtyChunks {
-    pub fn clear(&mut self) {
-        self.0.clear();
+impl ChunkUnloadQueue {
+    pub fn schedule(&mut self, chunk: IVec3, unload_time: f32) {
+        self.0.entry(chunk).or_insert(unload_time);
+    }
+
+    pub fn cancel(&mut self, chunk: &IVec3) {
+        self.0.remove(chunk);
+    }
+
+    pub fn drain_expired(&mut self, time: f32) -> Vec<IVec3> {
+        let expired: Vec<IVec3> = self
+            .0
+            .iter()
+            .filter(|(_, unload_time)| **unload_time <= time)
+            .map(|(chunk, _)| *chunk)
+            .collect();
+
+        for chunk in &expired {
+            self.0.remove(chunk);
+        }
+        expired
     }
+}
+
+#[derive(Resource, Default)]
+pub struct DirtyChunks(HashSet<IVec3>);
 
+impl DirtyChunks {
     pub fn mark_dirty(&mut self, chunk: IVec3) {
         self.0.insert(chunk);
     }
 
+    // Voxels on a chunk border are also part of the neighbouring chunk's mesh.
+    pub fn mark_voxel_dirty(&mut self, position: IVec3) {
+        let chunk = World::world_to_chunk_position(position);
+        let local = World::world_to_chunk_voxel_position(position);
+        self.mark_dirty(chunk);
+
+        for (axis, offset) in [IVec3::X, IVec3::Y, IVec3::Z].into_iter().enumerate() {
+            if local[axis] == 0 {
+                self.mark_dirty(chunk - offset);
+            } else if local[axis] == CHUNK_SIZE_I32 - 1 {
+                self.mark_dirty(chunk + offset);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, chunk: &IVec3) {
+        self.0.remove(chunk);
+    }
+
     pub fn iter_dirty(&self) -> impl Iterator<Item = &IVec3> {
         self.0.iter()
     }
 
-    //pub fn num_dirty(&self) -> usize {
-    //    self.0.len()
-    //}
+    pub fn num_dirty(&self) -> usize {
+        self.0.len()
+    }
 }
 
 #[derive(Resource, Default)]
 pub struct ChunkEntities(HashMap<IVec3, Entity>);
 
 impl ChunkEntities {
-    pub fn keys(&self) -> Keys<IVec3, Entity> {
+    pub fn keys(&self) -> Keys<'_, IVec3, Entity> {
         self.0.keys()
     }
 
@@ -71,3 +196,16 @@ impl ChunkEntities {
         self.0.remove(position)
     }
 }
+
+#[test]
+fn unload_queue_keeps_first_schedule_time() {
+    let mut queue = ChunkUnloadQueue::default();
+    queue.schedule(IVec3::ZERO, 1.0);
+    queue.schedule(IVec3::ZERO, 5.0);
+    queue.schedule(IVec3::X, 2.0);
+    queue.cancel(&IVec3::X);
+
+    assert!(queue.drain_expired(0.5).is_empty());
+    assert_eq!(queue.drain_expired(1.0), vec![IVec3::ZERO]);
+    assert!(queue.drain_expired(10.0).is_empty());
+}
@@ -0,0 +1,131 @@
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics},
+    prelude::*,
+};
+
+use crate::world::World;
+
+use super::data::*;
+
+const TIMING_HISTORY_LENGTH: usize = 256;
+
+pub struct ChunkDiagnosticsPlugin;
+
+impl ChunkDiagnosticsPlugin {
+    pub const STATES: [(ChunkState, DiagnosticId); 6] = [
+        (
+            ChunkState::Queued,
+            DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a401),
+        ),
+        (
+            ChunkState::Generating,
+            DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a402),
+        ),
+        (
+            ChunkState::Generated,
+            DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a403),
+        ),
+        (
+            ChunkState::NeighboursReady,
+            DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a404),
+        ),
+        (
+            ChunkState::Meshing,
+            DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a405),
+        ),
+        (
+            ChunkState::Meshed,
+            DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a406),
+        ),
+    ];
+    pub const DIRTY: DiagnosticId =
+        DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a407);
+    pub const GENERATION_TIME: DiagnosticId =
+        DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a408);
+    pub const MESHING_TIME: DiagnosticId =
+        DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a409);
+    pub const VERTICES: DiagnosticId =
+        DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a40a);
+    pub const TRIANGLES: DiagnosticId =
+        DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a40b);
+    pub const VOXEL_MEMORY: DiagnosticId =
+        DiagnosticId::from_u128(0x6a3f_12c4_8d0e_4b71_9f25_3c1e_0b7d_a40c);
+
+    fn setup(mut diagnostics: ResMut<Diagnostics>) {
+        for (state, id) in Self::STATES {
+            let name = format!("chunks_{}", format!("{state:?}").to_lowercase());
+            diagnostics.add(Diagnostic::new(id, name, 1));
+        }
+
+        diagnostics.add(Diagnostic::new(Self::DIRTY, "chunks_dirty", 1));
+        diagnostics.add(
+            Diagnostic::new(
+                Self::GENERATION_TIME,
+                "chunk_generation_time",
+                TIMING_HISTORY_LENGTH,
+            )
+            .with_suffix("ms"),
+        );
+        diagnostics.add(
+            Diagnostic::new(
+                Self::MESHING_TIME,
+                "chunk_meshing_time",
+                TIMING_HISTORY_LENGTH,
+            )
+            .with_suffix("ms"),
+        );
+        diagnostics.add(Diagnostic::new(Self::VERTICES, "chunk_vertices", 1));
+        diagnostics.add(Diagnostic::new(Self::TRIANGLES, "chunk_triangles", 1));
+        diagnostics
+            .add(Diagnostic::new(Self::VOXEL_MEMORY, "chunk_voxel_memory", 1).with_suffix("MiB"));
+    }
+
+    fn measure(
+        mut diagnostics: ResMut<Diagnostics>,
+        chunks: Query<(&ChunkState, Option<&ChunkMeshInfo>)>,
+        dirty_chunks: Res<DirtyChunks>,
+        world: Res<World>,
+    ) {
+        let mut state_counts = [0usize; 6];
+        let mut vertices = 0;
+        let mut triangles = 0;
+
+        for (state, mesh_info) in &chunks {
+            state_counts[*state as usize] += 1;
+
+            if let Some(mesh_info) = mesh_info {
+                vertices += mesh_info.vertex_count;
+                triangles += mesh_info.triangle_count;
+            }
+        }
+
+        for (state, id) in Self::STATES {
+            diagnostics.add_measurement(id, || state_counts[state as usize] as f64);
+        }
+
+        diagnostics.add_measurement(Self::DIRTY, || dirty_chunks.num_dirty() as f64);
+        diagnostics.add_measurement(Self::VERTICES, || vertices as f64);
+        diagnostics.add_measurement(Self::TRIANGLES, || triangles as f64);
+        diagnostics.add_measurement(Self::VOXEL_MEMORY, || {
+            world.voxel_memory_usage() as f64 / (1024.0 * 1024.0)
+        });
+    }
+}
+
+impl Plugin for ChunkDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(Self::setup)
+            .add_system_to_stage(CoreStage::Last, Self::measure);
+    }
+}
+
+// `p` in `0.0..=1.0`, over the values kept in the diagnostic's history.
+pub fn diagnostic_percentile(diagnostic: &Diagnostic, p: f64) -> Option<f64> {
+    let mut values: Vec<f64> = diagnostic.values().copied().collect();
+    if values.is_empty() {
+        return None;
+    }
+
+    values.sort_unstable_by(f64::total_cmp);
+    Some(values[((values.len() - 1) as f64 * p).round() as usize])
+}
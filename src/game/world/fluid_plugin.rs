@@ -0,0 +1,32 @@
+use bevy::{prelude::*, time::FixedTimestep};
+
+use crate::world::{FluidSimulation, World};
+
+use super::data::*;
+
+const FLUID_TICK_INTERVAL: f64 = 0.25;
+
+fn tick_fluids(
+    mut simulation: ResMut<FluidSimulation>,
+    mut dirty_chunks: ResMut<DirtyChunks>,
+    mut world: ResMut<World>,
+) {
+    if simulation.is_idle() {
+        return;
+    }
+
+    for position in simulation.tick(&mut world) {
+        dirty_chunks.mark_voxel_dirty(position);
+    }
+}
+
+pub struct FluidPlugin;
+impl Plugin for FluidPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FluidSimulation>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(FixedTimestep::step(FLUID_TICK_INTERVAL))
+                .with_system(tick_fluids),
+        );
+    }
+}
@@ -1,40 +1,116 @@
-use bevy::{prelude::*, tasks::*};
+use std::time::Instant;
+
+use bevy::{diagnostic::Diagnostics, prelude::*, render::primitives::Frustum, tasks::*};
 use futures_lite::future::{block_on, poll_once};
 
-use crate::world::{Chunk, World};
+use crate::{
+    data::voxel_face::FACES,
+    game::{CameraState, Settings},
+    world::{storage, Chunk, World},
+};
 
-use super::data::*;
+use super::{data::*, diagnostics_plugin::ChunkDiagnosticsPlugin, priority::ChunkPriority};
 
 fn queue_chunk_terrain_generation(
     mut commands: Commands,
-    new_chunks: Query<(Entity, &ChunkComponent), Added<ChunkComponent>>,
+    settings: Res<Settings>,
+    camera: Query<(&Transform, Option<&Frustum>), With<CameraState>>,
+    mut chunks: Query<(&ChunkComponent, &mut ChunkState)>,
+    chunk_entities: Res<ChunkEntities>,
+    save_directory: Res<WorldSaveDirectory>,
+    world: Res<World>,
 ) {
+    let (transform, frustum) = camera.single();
+    let priority = ChunkPriority::new(transform.translation, frustum);
+
+    let mut positions: Vec<IVec3> = chunks
+        .iter()
+        .filter(|(_, state)| **state == ChunkState::Queued)
+        .map(|(chunk, _)| chunk.0)
+        .collect();
     let task_pool = AsyncComputeTaskPool::get();
+    let seed = world.seed();
 
-    for (entity, chunk_component) in &new_chunks {
-        let chunk_position = chunk_component.0;
+    for &chunk_position in priority.select(&mut positions, settings.chunk_generation_budget) {
+        let directory = save_directory.0.clone();
         let task = task_pool.spawn(async move {
-            let chunk = Chunk::generate_at(chunk_position).unwrap();
-            chunk
+            let start = Instant::now();
+            let saved_chunk =
+                storage::load_chunk(&directory, chunk_position).unwrap_or_else(|err| {
+                    warn!("Failed to load chunk {chunk_position}: {err}");
+                    None
+                });
+            let chunk =
+                saved_chunk.unwrap_or_else(|| Chunk::generate_at(chunk_position, seed).unwrap());
+            (chunk, start.elapsed())
         });
 
+        let entity = chunk_entities.entity(&chunk_position).unwrap();
         commands.entity(entity).insert(TerrainGenerationTask(task));
+        *chunks.get_mut(entity).unwrap().1 = ChunkState::Generating;
     }
 }
 
 fn process_chunk_terrain_generation(
     mut commands: Commands,
     mut generating_chunks: Query<(Entity, &ChunkComponent, &mut TerrainGenerationTask)>,
+    mut states: Query<&mut ChunkState>,
+    chunk_entities: Res<ChunkEntities>,
     mut dirty_chunks: ResMut<DirtyChunks>,
+    mut diagnostics: ResMut<Diagnostics>,
     mut world: ResMut<World>,
 ) {
     for (entity, chunk_component, mut task) in &mut generating_chunks {
-        if let Some(chunk) = block_on(poll_once(&mut task.0)) {
+        if let Some((chunk, duration)) = block_on(poll_once(&mut task.0)) {
             commands.entity(entity).remove::<TerrainGenerationTask>();
+            diagnostics.add_measurement(ChunkDiagnosticsPlugin::GENERATION_TIME, || {
+                duration.as_secs_f64() * 1000.0
+            });
 
             let chunk_position = chunk_component.0;
             world.set_chunk(chunk_position, chunk);
-            dirty_chunks.mark_dirty(chunk_position);
+            *states.get_mut(entity).unwrap() = ChunkState::Generated;
+
+            // Neighbours that were already meshed without this chunk need to be meshed again.
+            for face in FACES {
+                let neighbour_position = chunk_position + face.normal();
+                let Some(neighbour) = chunk_entities.entity(&neighbour_position) else {
+                    continue;
+                };
+
+                if states.get(neighbour).is_ok_and(|state| state.can_mesh()) {
+                    dirty_chunks.mark_dirty(neighbour_position);
+                }
+            }
+        }
+    }
+}
+
+// A chunk can be meshed once every neighbour is either generated or not loaded at all.
+fn update_chunk_readiness(
+    mut dirty_chunks: ResMut<DirtyChunks>,
+    chunk_entities: Res<ChunkEntities>,
+    chunks: Query<(Entity, &ChunkComponent)>,
+    mut states: Query<&mut ChunkState>,
+) {
+    for (entity, chunk_component) in &chunks {
+        if states.get(entity).ok().copied() != Some(ChunkState::Generated) {
+            continue;
+        }
+
+        let neighbours_ready = FACES.iter().all(|face| {
+            chunk_entities
+                .entity(&(chunk_component.0 + face.normal()))
+                .is_none_or(|neighbour| {
+                    states
+                        .get(neighbour)
+                        .is_ok_and(|state| state.is_generated())
+                })
+        });
+
+        if neighbours_ready {
+            *states.get_mut(entity).unwrap() = ChunkState::NeighboursReady;
+            dirty_chunks.mark_dirty(chunk_component.0);
         }
     }
 }
@@ -47,9 +123,8 @@ impl Plugin for ChunkGenerationPlugin {
             ChunkGenerationStage,
             SystemStage::parallel()
                 .with_system(queue_chunk_terrain_generation)
-                .with_system(
-                    process_chunk_terrain_generation.after(queue_chunk_terrain_generation),
-                ),
+                .with_system(process_chunk_terrain_generation.after(queue_chunk_terrain_generation))
+                .with_system(update_chunk_readiness.after(process_chunk_terrain_generation)),
         );
     }
 }
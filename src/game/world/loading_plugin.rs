@@ -1,87 +1,130 @@
-use bevy::{prelude::*, utils::FloatOrd};
+use std::collections::HashSet;
 
-use crate::{game::CameraState, world::World};
+use bevy::{prelude::*, render::primitives::Frustum};
 
-use super::data::*;
+use crate::{
+    game::{CameraState, Settings},
+    world::World,
+};
+
+use super::{data::*, priority::ChunkPriority};
 
 pub fn destroy_chunks(
     mut commands: Commands,
     mut chunk_command_queue: ResMut<ChunkCommandQueue>,
     mut chunk_entities: ResMut<ChunkEntities>,
+    mut dirty_chunks: ResMut<DirtyChunks>,
     mut world: ResMut<World>,
 ) {
     for position in chunk_command_queue.destroy.drain(..) {
+        // Despawning drops any in-flight generation or meshing task, which cancels it.
         let entity = chunk_entities.detach_entity(&position).unwrap();
-        commands.entity(entity).despawn();
+        commands.entity(entity).despawn_recursive();
 
+        dirty_chunks.remove(&position);
         world.remove_chunk(position);
     }
 }
 
 pub fn create_chunks(
     mut commands: Commands,
+    settings: Res<Settings>,
+    camera: Query<(&Transform, Option<&Frustum>), With<CameraState>>,
     mut chunk_command_queue: ResMut<ChunkCommandQueue>,
     mut chunk_entities: ResMut<ChunkEntities>,
 ) {
-    for position in chunk_command_queue.create.drain(..) {
-        let entity = commands.spawn(ChunkComponent(position));
+    let (transform, frustum) = camera.single();
+    let priority = ChunkPriority::new(transform.translation, frustum);
+
+    let queue = &mut chunk_command_queue.create;
+    let count = priority.select(queue, settings.chunk_spawn_budget).len();
+
+    for position in queue.drain(..count) {
+        let entity = commands.spawn((ChunkComponent(position), ChunkState::Queued));
         chunk_entities.attach_entity(position, entity.id());
     }
 }
 
-const VIEW_DISTANCE: i32 = 8;
-const HALF_VIEW_DISTANCE: i32 = 4;
+// The loaded chunks only need to be recomputed when a loader enters another chunk, changes its
+// radius, or is added or removed.
+#[derive(Default, PartialEq)]
+pub struct LoaderSnapshot {
+    unload_distance_margin: i32,
+    loaders: Vec<(Entity, IVec3, ChunkLoader)>,
+}
 
-pub fn update_chunks_within_view_distance(
-    mut camera: Query<(&Transform, &mut CameraState)>,
+pub fn update_loaded_chunks(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    loaders: Query<(Entity, &GlobalTransform, &ChunkLoader)>,
+    mut previous: Local<Option<LoaderSnapshot>>,
     mut chunk_command_queue: ResMut<ChunkCommandQueue>,
+    mut unload_queue: ResMut<ChunkUnloadQueue>,
     chunk_entities: Res<ChunkEntities>,
 ) {
-    let (transform, mut state) = camera.single_mut();
-    if !state.should_load_chunks {
+    let mut snapshot = LoaderSnapshot {
+        unload_distance_margin: settings.unload_distance_margin,
+        loaders: loaders
+            .iter()
+            .map(|(entity, transform, loader)| {
+                let position = transform.translation().as_ivec3();
+                (entity, World::world_to_chunk_position(position), *loader)
+            })
+            .collect(),
+    };
+    snapshot
+        .loaders
+        .sort_unstable_by_key(|(entity, _, _)| *entity);
+
+    if previous.as_ref() == Some(&snapshot) {
         return;
     }
 
-    state.should_load_chunks = false;
-
-    let player_position = World::world_to_chunk_position(transform.translation.as_ivec3());
-
-    let mut within_view_distance_positions = vec![];
+    let mut load_positions = HashSet::new();
+    let mut keep_positions = HashSet::new();
+
+    for (_, chunk_position, loader) in &snapshot.loaders {
+        load_positions.extend(World::chunks_within_radius(
+            *chunk_position,
+            loader.radius,
+            loader.vertical_radius,
+        ));
+
+        // Chunks are kept around a bit farther than they're loaded so moving back and forth
+        // across a chunk border doesn't keep loading and unloading the same chunks.
+        keep_positions.extend(World::chunks_within_radius(
+            *chunk_position,
+            loader.radius + snapshot.unload_distance_margin,
+            loader.vertical_radius + snapshot.unload_distance_margin,
+        ));
+    }
 
     chunk_command_queue.create.clear();
-    chunk_command_queue.destroy.clear();
-
-    for x in -VIEW_DISTANCE..=VIEW_DISTANCE {
-        for z in -VIEW_DISTANCE..=VIEW_DISTANCE {
-            if x.pow(2) + z.pow(2) >= VIEW_DISTANCE.pow(2) {
-                continue;
-            }
-
-            for y in -HALF_VIEW_DISTANCE..=HALF_VIEW_DISTANCE {
-                let chunk_position = player_position + IVec3::new(x, y, z);
-
-                if chunk_entities.entity(&chunk_position).is_none() {
-                    chunk_command_queue.create.push(chunk_position);
-                }
-
-                within_view_distance_positions.push(chunk_position);
-            }
-        }
-    }
+    chunk_command_queue.create.extend(
+        load_positions
+            .iter()
+            .filter(|position| chunk_entities.entity(position).is_none()),
+    );
 
+    let unload_time = time.elapsed_seconds() + settings.chunk_unload_delay;
     for loaded_chunk_position in chunk_entities.keys() {
-        if !within_view_distance_positions.contains(loaded_chunk_position) {
-            chunk_command_queue.destroy.push(*loaded_chunk_position);
+        if keep_positions.contains(loaded_chunk_position) {
+            unload_queue.cancel(loaded_chunk_position);
+        } else {
+            unload_queue.schedule(*loaded_chunk_position, unload_time);
         }
     }
 
-    chunk_command_queue
-        .create
-        .sort_unstable_by_key(|key| FloatOrd(key.as_vec3().distance(player_position.as_vec3())));
+    *previous = Some(snapshot);
 }
 
-pub fn clear_dirty_chunks(mut dirty_chunks: ResMut<DirtyChunks>) {
-    dirty_chunks.clear();
+pub fn unload_expired_chunks(
+    time: Res<Time>,
+    mut chunk_command_queue: ResMut<ChunkCommandQueue>,
+    mut unload_queue: ResMut<ChunkUnloadQueue>,
+) {
+    let expired = unload_queue.drain_expired(time.elapsed_seconds());
+    chunk_command_queue.destroy.extend(expired);
 }
 
 pub struct ChunkLoadingPlugin;
@@ -91,10 +134,10 @@ impl Plugin for ChunkLoadingPlugin {
             CoreStage::Update,
             ChunkLoadingStage,
             SystemStage::parallel()
-                .with_system(update_chunks_within_view_distance)
-                .with_system(create_chunks.after(update_chunks_within_view_distance)),
+                .with_system(update_loaded_chunks)
+                .with_system(create_chunks.after(update_loaded_chunks))
+                .with_system(unload_expired_chunks.after(update_loaded_chunks)),
         )
-        .add_system_to_stage(CoreStage::Last, destroy_chunks)
-        .add_system_to_stage(CoreStage::Last, clear_dirty_chunks);
+        .add_system_to_stage(CoreStage::Last, destroy_chunks);
     }
 }
@@ -1,78 +1,181 @@
-use bevy::{prelude::*, render::primitives::Aabb, tasks::AsyncComputeTaskPool};
+use std::time::Instant;
+
+use bevy::{
+    diagnostic::Diagnostics,
+    prelude::*,
+    render::primitives::{Aabb, Frustum},
+    tasks::AsyncComputeTaskPool,
+};
 use futures_lite::future;
 
 use crate::{
     data::constants::*,
+    game::{CameraState, Settings},
     rendering::*,
-    world::{meshing, World},
+    world::{
+        meshing::{self, ChunkMeshData},
+        BiomeTints, World,
+    },
 };
 
-use super::data::*;
+use super::{data::*, diagnostics_plugin::ChunkDiagnosticsPlugin, priority::ChunkPriority};
+
+fn setup_chunk_materials(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ChunkMaterial>>,
+    texture_atlas: Res<ChunkTextureAtlas>,
+) {
+    let mut add =
+        |alpha_mode| materials.add(ChunkMaterial::new(texture_atlas.0.clone_weak(), alpha_mode));
+
+    commands.insert_resource(ChunkMaterials {
+        opaque: add(AlphaMode::Opaque),
+        cutout: add(AlphaMode::Mask(0.5)),
+        translucent: add(AlphaMode::Blend),
+    });
+}
 
 fn prepare_new_chunks(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ChunkMaterial>>,
+    materials: Res<ChunkMaterials>,
     new_chunks: Query<(Entity, &ChunkComponent), Added<ChunkComponent>>,
-    texture_atlas: Res<ChunkTextureAtlas>,
 ) {
     for (entity, chunk_component) in new_chunks.iter() {
         let chunk_position = chunk_component.0;
         let chunk_world_position = (chunk_position * CHUNK_SIZE_I32).as_vec3();
+        let aabb = Aabb::from_min_max(Vec3::ZERO, Vec3::splat(CHUNK_SIZE_I32 as f32));
+
+        let mut spawn_layer = |material: &Handle<ChunkMaterial>, transform| {
+            commands
+                .spawn(MaterialMeshBundle {
+                    transform,
+                    material: material.clone(),
+                    mesh: meshes.add(meshing::generate_empty_chunk_mesh()),
+                    ..Default::default()
+                })
+                .insert(Aabb::from_min_max(
+                    -transform.translation,
+                    Vec3::splat(CHUNK_SIZE_I32 as f32) - transform.translation,
+                ))
+                .id()
+        };
+
+        let layers = ChunkLayerEntities {
+            cutout: spawn_layer(&materials.cutout, Transform::IDENTITY),
+            translucent: spawn_layer(
+                &materials.translucent,
+                Transform::from_translation(ChunkMeshData::TRANSLUCENT_ORIGIN),
+            ),
+        };
 
         commands
             .entity(entity)
             .insert(MaterialMeshBundle {
                 transform: Transform::from_translation(chunk_world_position),
-                material: materials.add(ChunkMaterial {
-                    texture_atlas: texture_atlas.0.clone_weak(),
-                }),
+                material: materials.opaque.clone(),
                 mesh: meshes.add(meshing::generate_empty_chunk_mesh()),
                 visibility: Visibility::INVISIBLE,
                 ..Default::default()
             })
-            .insert(Aabb::from_min_max(
-                Vec3::ZERO,
-                Vec3::splat(CHUNK_SIZE_I32 as f32),
-            ));
+            .insert(aabb)
+            .insert(layers)
+            .push_children(&[layers.cutout, layers.translucent]);
     }
 }
 
 fn queue_chunk_meshing(
     mut commands: Commands,
-    dirty_chunks: Res<DirtyChunks>,
+    settings: Res<Settings>,
+    camera: Query<(&Transform, Option<&Frustum>), With<CameraState>>,
+    mut dirty_chunks: ResMut<DirtyChunks>,
     chunk_entities: Res<ChunkEntities>,
+    mut states: Query<&mut ChunkState>,
     world: Res<World>,
 ) {
+    let (transform, frustum) = camera.single();
+    let priority = ChunkPriority::new(transform.translation, frustum);
+
+    // Chunks that aren't ready yet get marked dirty again once their neighbours are generated.
+    let mut positions: Vec<IVec3> = dirty_chunks.iter_dirty().copied().collect();
+    positions.retain(|position| {
+        let ready = chunk_entities
+            .entity(position)
+            .and_then(|entity| states.get(entity).ok())
+            .is_some_and(|state| state.can_mesh());
+        if !ready {
+            dirty_chunks.remove(position);
+        }
+        ready
+    });
+
     let task_pool = AsyncComputeTaskPool::get();
 
-    for chunk_position in dirty_chunks.iter_dirty() {
+    for chunk_position in priority.select(&mut positions, settings.chunk_meshing_budget) {
         let entity = chunk_entities.entity(chunk_position).unwrap();
         let chunk = world.get_chunk(*chunk_position).unwrap().clone();
+        let neighbours = meshing::ChunkNeighbours::from_world(&world, *chunk_position);
+        let (seed, blend_radius) = (world.seed(), settings.biome_blend_radius);
+        let chunk_position = *chunk_position;
 
-        let task = task_pool.spawn(async move { meshing::generate_chunk_mesh(&chunk) });
+        let task = task_pool.spawn(async move {
+            let start = Instant::now();
+            let tints = BiomeTints::new(seed, chunk_position, blend_radius);
+            let mesh_data = meshing::generate_chunk_mesh_data(&chunk, &neighbours, &tints);
+            let info = ChunkMeshInfo {
+                vertex_count: mesh_data.vertex_count(),
+                triangle_count: mesh_data.triangle_count(),
+            };
+
+            ChunkMeshingResult {
+                mesh: mesh_data.opaque.into(),
+                cutout_mesh: mesh_data.cutout.into(),
+                translucent_mesh: mesh_data.translucent.into(),
+                info,
+                duration: start.elapsed(),
+            }
+        });
 
         commands.entity(entity).insert(ChunkMeshingTask(task));
+        *states.get_mut(entity).unwrap() = ChunkState::Meshing;
+        dirty_chunks.remove(&chunk_position);
     }
 }
 
 fn process_mesh_tasks(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut query: Query<
-        (
-            Entity,
-            &Handle<Mesh>,
-            &mut ChunkMeshingTask,
-            &mut Visibility,
-        ),
-        With<ChunkComponent>,
-    >,
+    mut diagnostics: ResMut<Diagnostics>,
+    mut query: Query<(
+        Entity,
+        &ChunkLayerEntities,
+        &mut ChunkMeshingTask,
+        &mut ChunkState,
+        &mut Visibility,
+    )>,
+    mesh_handles: Query<&Handle<Mesh>>,
 ) {
-    for (entity, handle, mut task, mut visibility) in &mut query {
-        if let Some(mesh) = future::block_on(future::poll_once(&mut task.0)) {
-            commands.entity(entity).remove::<ChunkMeshingTask>();
-            *meshes.get_mut(handle).unwrap() = mesh;
+    for (entity, layers, mut task, mut state, mut visibility) in &mut query {
+        if let Some(result) = future::block_on(future::poll_once(&mut task.0)) {
+            commands
+                .entity(entity)
+                .remove::<ChunkMeshingTask>()
+                .insert(result.info);
+            diagnostics.add_measurement(ChunkDiagnosticsPlugin::MESHING_TIME, || {
+                result.duration.as_secs_f64() * 1000.0
+            });
+
+            *state = ChunkState::Meshed;
+            let layer_meshes = [
+                (entity, result.mesh),
+                (layers.cutout, result.cutout_mesh),
+                (layers.translucent, result.translucent_mesh),
+            ];
+            for (layer_entity, mesh) in layer_meshes {
+                *meshes
+                    .get_mut(mesh_handles.get(layer_entity).unwrap())
+                    .unwrap() = mesh;
+            }
             visibility.is_visible = true;
         }
     }
@@ -81,17 +184,18 @@ fn process_mesh_tasks(
 pub struct ChunkMeshingPlugin;
 impl Plugin for ChunkMeshingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_stage_after(
-            ChunkGenerationStage,
-            ChunkMeshingPrepareStage,
-            SystemStage::single(prepare_new_chunks),
-        )
-        .add_stage_after(
-            ChunkMeshingPrepareStage,
-            ChunkMeshingStage,
-            SystemStage::parallel()
-                .with_system(queue_chunk_meshing)
-                .with_system(process_mesh_tasks.after(queue_chunk_meshing)),
-        );
+        app.add_startup_system(setup_chunk_materials)
+            .add_stage_after(
+                ChunkGenerationStage,
+                ChunkMeshingPrepareStage,
+                SystemStage::single(prepare_new_chunks),
+            )
+            .add_stage_after(
+                ChunkMeshingPrepareStage,
+                ChunkMeshingStage,
+                SystemStage::parallel()
+                    .with_system(queue_chunk_meshing)
+                    .with_system(process_mesh_tasks.after(queue_chunk_meshing)),
+            );
     }
 }
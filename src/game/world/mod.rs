@@ -1,7 +1,12 @@
 mod data;
+mod diagnostics_plugin;
+mod fluid_plugin;
 mod generation_plugin;
 mod loading_plugin;
 mod meshing_plugin;
+mod priority;
 mod world_plugin;
 
+pub use data::*;
+pub use diagnostics_plugin::*;
 pub use world_plugin::*;
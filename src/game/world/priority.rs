@@ -0,0 +1,71 @@
+use bevy::{
+    math::Vec3A,
+    prelude::*,
+    render::primitives::{Frustum, Sphere},
+    utils::FloatOrd,
+};
+
+use crate::data::constants::*;
+
+// Chunks outside of the view frustum are handled as if they were this many times farther away.
+const OUT_OF_VIEW_PRIORITY_FACTOR: f32 = 3.0;
+
+pub struct ChunkPriority<'a> {
+    origin: Vec3,
+    frustum: Option<&'a Frustum>,
+}
+
+impl<'a> ChunkPriority<'a> {
+    pub fn new(origin: Vec3, frustum: Option<&'a Frustum>) -> Self {
+        Self { origin, frustum }
+    }
+
+    // Lower values are handled first.
+    pub fn of(&self, chunk_position: IVec3) -> f32 {
+        let half_size = CHUNK_SIZE_I32 as f32 * 0.5;
+        let center = (chunk_position * CHUNK_SIZE_I32).as_vec3() + half_size;
+        let distance = center.distance(self.origin) / CHUNK_SIZE_I32 as f32;
+
+        let bounds = Sphere {
+            center: Vec3A::from(center),
+            radius: half_size * 3f32.sqrt(),
+        };
+        let in_view = self
+            .frustum
+            .is_none_or(|frustum| frustum.intersects_sphere(&bounds, false));
+
+        if in_view {
+            distance
+        } else {
+            distance * OUT_OF_VIEW_PRIORITY_FACTOR
+        }
+    }
+
+    // Moves the `count` highest priority positions to the front of `positions` and returns them.
+    pub fn select<'p>(&self, positions: &'p mut [IVec3], count: usize) -> &'p [IVec3] {
+        let count = count.min(positions.len());
+        if count > 0 && count < positions.len() {
+            positions
+                .select_nth_unstable_by_key(count - 1, |position| FloatOrd(self.of(*position)));
+        }
+        &positions[..count]
+    }
+}
+
+#[test]
+fn selects_closest_chunks_first() {
+    let priority = ChunkPriority::new(Vec3::splat(16.0), None);
+    let mut positions = vec![
+        IVec3::new(5, 0, 0),
+        IVec3::new(0, 0, 1),
+        IVec3::new(-3, 0, 0),
+        IVec3::ZERO,
+        IVec3::new(0, 2, 0),
+    ];
+
+    let mut selected = priority.select(&mut positions, 2).to_vec();
+    selected.sort_unstable_by_key(|position| position.to_array());
+
+    assert_eq!(selected, vec![IVec3::ZERO, IVec3::new(0, 0, 1)]);
+    assert_eq!(positions.len(), 5);
+}
@@ -1,65 +1,36 @@
-use bevy::{prelude::*, window::CursorGrabMode};
+use bevy::prelude::*;
 
-use crate::{data::VoxelType, game::CameraState, world::World};
+use crate::world::{storage, World};
 
 use super::{
-    data::*, generation_plugin::ChunkGenerationPlugin, loading_plugin::ChunkLoadingPlugin,
+    data::*, diagnostics_plugin::ChunkDiagnosticsPlugin, fluid_plugin::FluidPlugin,
+    generation_plugin::ChunkGenerationPlugin, loading_plugin::ChunkLoadingPlugin,
     meshing_plugin::ChunkMeshingPlugin,
 };
 
 pub struct WorldPlugin;
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<ChunkCommandQueue>()
+        let save_directory = WorldSaveDirectory::default();
+        let world = match storage::load_seed(&save_directory.0) {
+            Ok(Some(seed)) => World::with_seed(seed),
+            Ok(None) => World::new(),
+            Err(err) => {
+                warn!("Failed to load the world seed: {err}");
+                World::new()
+            }
+        };
+
+        app.insert_resource(save_directory)
+            .insert_resource(world)
+            .init_resource::<ChunkCommandQueue>()
             .init_resource::<ChunkEntities>()
+            .init_resource::<ChunkUnloadQueue>()
             .init_resource::<DirtyChunks>()
-            .init_resource::<World>()
             .add_plugin(ChunkLoadingPlugin)
             .add_plugin(ChunkGenerationPlugin)
             .add_plugin(ChunkMeshingPlugin)
-            .add_system(place_and_remove_voxels);
+            .add_plugin(ChunkDiagnosticsPlugin)
+            .add_plugin(FluidPlugin);
     }
 }
-
-fn place_and_remove_voxels(
-    mut dirty_chunks: ResMut<DirtyChunks>,
-    mut world: ResMut<World>,
-    windows: Res<Windows>,
-    mouse_button: Res<Input<MouseButton>>,
-    query: Query<&Transform, With<CameraState>>,
-) {
-    let Some(window) = windows.get_primary() else {
-        return;
-    };
-
-    if window.cursor_grab_mode() != CursorGrabMode::Confined {
-        return;
-    }
-
-    let (left_pressed, right_pressed) = (
-        mouse_button.pressed(MouseButton::Left),
-        mouse_button.pressed(MouseButton::Right),
-    );
-    if !left_pressed && !right_pressed {
-        return;
-    }
-
-    let transform = query.single();
-
-    let Some(hit) = world.raytrace(transform.translation, transform.forward(), 30.0) else {
-        return;
-    };
-
-    let chunk_position = World::world_to_chunk_position(hit.voxel_position);
-
-    if left_pressed {
-        world.set_voxel(VoxelType::Air, hit.voxel_position);
-    }
-
-    if right_pressed {
-        let voxel_position = hit.voxel_position + hit.face.normal();
-        world.set_voxel(VoxelType::Stone, voxel_position);
-    }
-
-    dirty_chunks.mark_dirty(chunk_position);
-}
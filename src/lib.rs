@@ -0,0 +1,6 @@
+pub mod data;
+#[cfg(feature = "render")]
+pub mod rendering;
+pub mod world;
+
+pub use glam;
@@ -1,31 +1,41 @@
-mod data;
 mod debug;
 mod game;
-mod rendering;
-mod world;
 
-use bevy::{asset::LoadState, prelude::*, window::PresentMode};
+use bevy::{asset::LoadState, prelude::*};
 use debug::*;
 use game::*;
-use rendering::{ChunkMaterial, ChunkTextureAtlas};
+use rendering::{ChunkMaterial, ChunkTextureAtlas, OutlineMaterial};
+use voxelands::{data, rendering, world};
 
 fn main() {
+    let settings = Settings::load();
+
     App::new()
         .add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
                     window: WindowDescriptor {
                         title: String::from("Voxelands"),
-                        present_mode: PresentMode::AutoNoVsync,
+                        present_mode: settings.present_mode(),
                         ..Default::default()
                     },
                     ..Default::default()
                 })
                 .set(ImagePlugin::default_nearest()),
         )
+        .insert_resource(settings)
         .add_plugin(MaterialPlugin::<ChunkMaterial>::default())
+        .add_plugin(MaterialPlugin::<OutlineMaterial>::default())
+        .add_plugin(SettingsPlugin)
+        .add_plugin(SkyPlugin)
+        .add_plugin(InputBindingsPlugin)
         .add_plugin(DebugPlugin)
         .add_plugin(CameraPlugin)
+        .add_plugin(PlayerPlugin)
+        .add_plugin(HotbarPlugin)
+        .add_plugin(InteractionPlugin)
+        .add_plugin(HighlightPlugin)
+        .add_plugin(ConsolePlugin)
         .add_plugin(WorldPlugin)
         .add_startup_system_to_stage(StartupStage::PreStartup, setup_texture_atlas)
         .add_system(prepare_texture_atlas)
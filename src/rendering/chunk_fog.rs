@@ -0,0 +1,42 @@
+// The size checks generated by `ShaderType` trip the dead code lint on recent compilers.
+#![allow(dead_code)]
+
+use bevy::{prelude::*, render::render_resource::ShaderType};
+
+// Fog blending chunks into the sky colour with distance from the camera.
+#[derive(ShaderType, Debug, Clone, Copy, PartialEq, Default)]
+pub struct ChunkFog {
+    pub color: Vec4,
+    // Linear fog starts at `start` and is opaque at `end`.
+    pub start: f32,
+    pub end: f32,
+    // Exponential fog density, per block.
+    pub density: f32,
+    pub mode: u32,
+}
+
+impl ChunkFog {
+    pub const MODE_OFF: u32 = 0;
+    pub const MODE_LINEAR: u32 = 1;
+    pub const MODE_EXPONENTIAL: u32 = 2;
+
+    pub fn linear(color: Color, start: f32, end: f32) -> Self {
+        Self {
+            color: color.as_linear_rgba_f32().into(),
+            start,
+            end,
+            density: 0.0,
+            mode: Self::MODE_LINEAR,
+        }
+    }
+
+    pub fn exponential(color: Color, density: f32) -> Self {
+        Self {
+            color: color.as_linear_rgba_f32().into(),
+            start: 0.0,
+            end: 0.0,
+            density,
+            mode: Self::MODE_EXPONENTIAL,
+        }
+    }
+}
@@ -7,6 +7,8 @@ use bevy::{
     },
 };
 
+use super::ChunkFog;
+
 #[derive(Resource, Clone, Default)]
 pub struct ChunkTextureAtlas(pub Handle<Image>);
 
@@ -16,14 +18,44 @@ pub struct ChunkMaterial {
     #[texture(0, dimension = "2d_array")]
     #[sampler(1)]
     pub texture_atlas: Handle<Image>,
+    // Fragments below this alpha are discarded, only used by cutout materials.
+    #[uniform(2)]
+    pub alpha_cutoff: f32,
+    #[uniform(3)]
+    pub fog: ChunkFog,
+    // Scales sky light, from the time of day.
+    #[uniform(4)]
+    pub sky_brightness: f32,
+    pub alpha_mode: AlphaMode,
 }
 
 impl ChunkMaterial {
+    pub fn new(texture_atlas: Handle<Image>, alpha_mode: AlphaMode) -> Self {
+        let alpha_cutoff = match alpha_mode {
+            AlphaMode::Mask(cutoff) => cutoff,
+            _ => 0.0,
+        };
+
+        Self {
+            texture_atlas,
+            alpha_cutoff,
+            fog: ChunkFog::default(),
+            sky_brightness: 1.0,
+            alpha_mode,
+        }
+    }
+
     pub const ATTRIBUTE_DATA: MeshVertexAttribute =
         MeshVertexAttribute::new("Data", 2 << 12, VertexFormat::Uint32);
+    pub const ATTRIBUTE_TINT: MeshVertexAttribute =
+        MeshVertexAttribute::new("Tint", (2 << 12) + 1, VertexFormat::Unorm8x4);
 }
 
 impl Material for ChunkMaterial {
+    fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
     fn fragment_shader() -> ShaderRef {
         "shaders/chunk.wgsl".into()
     }
@@ -40,7 +72,9 @@ impl Material for ChunkMaterial {
     ) -> Result<(), bevy::render::render_resource::SpecializedMeshPipelineError> {
         let vertex_layout = layout.get_layout(&[
             Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
-            ChunkMaterial::ATTRIBUTE_DATA.at_shader_location(1),
+            Mesh::ATTRIBUTE_UV_0.at_shader_location(1),
+            ChunkMaterial::ATTRIBUTE_DATA.at_shader_location(2),
+            ChunkMaterial::ATTRIBUTE_TINT.at_shader_location(3),
         ])?;
 
         descriptor.vertex.buffers = vec![vertex_layout];
@@ -1,3 +1,7 @@
+mod chunk_fog;
 mod chunk_material;
+mod outline_material;
 
+pub use chunk_fog::*;
 pub use chunk_material::*;
+pub use outline_material::*;
@@ -0,0 +1,106 @@
+use glam::*;
+
+use crate::data::constants::*;
+
+use super::generation::column_hash;
+
+// Size in columns of the cells the biome noise is interpolated between.
+const BIOME_CELL_SIZE: i32 = 96;
+
+const COLD_COLOR: Vec3 = Vec3::new(0.45, 0.62, 0.55);
+const DRY_COLOR: Vec3 = Vec3::new(0.75, 0.72, 0.35);
+const LUSH_COLOR: Vec3 = Vec3::new(0.36, 0.70, 0.25);
+
+// Smooth value noise between 0.0 and 1.0.
+fn value_noise(seed: u64, x: i32, z: i32) -> f32 {
+    let cell = IVec2::new(x, z).as_vec2() / BIOME_CELL_SIZE as f32;
+    let origin = cell.floor();
+    let t = cell - origin;
+    let t = t * t * (Vec2::splat(3.0) - 2.0 * t);
+
+    let corner = |dx: i32, dz: i32| {
+        let hash = column_hash(seed, origin.x as i32 + dx, origin.y as i32 + dz);
+        (hash >> 40) as f32 / (1u64 << 24) as f32
+    };
+
+    let bottom = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * t.x;
+    let top = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * t.x;
+    bottom + (top - bottom) * t.y
+}
+
+// Grass and foliage colour of a column, from its temperature and humidity.
+pub fn biome_color(seed: u64, x: i32, z: i32) -> Vec3 {
+    let temperature = value_noise(seed ^ 0x7465_6d70, x, z);
+    let humidity = value_noise(seed ^ 0x6875_6d69, x, z);
+    COLD_COLOR.lerp(DRY_COLOR.lerp(LUSH_COLOR, humidity), temperature)
+}
+
+// Biome colours of every column of a chunk, optionally averaged with the columns within
+// `blend_radius` so biome borders fade smoothly.
+#[derive(Debug, Clone)]
+pub struct BiomeTints {
+    colors: Box<[Vec3; CHUNK_SIZE * CHUNK_SIZE]>,
+}
+
+impl Default for BiomeTints {
+    fn default() -> Self {
+        Self::uniform(Vec3::ONE)
+    }
+}
+
+impl BiomeTints {
+    pub fn uniform(color: Vec3) -> Self {
+        Self {
+            colors: Box::new([color; CHUNK_SIZE * CHUNK_SIZE]),
+        }
+    }
+
+    pub fn new(seed: u64, chunk_position: IVec3, blend_radius: i32) -> Self {
+        let radius = blend_radius.max(0);
+        let size = CHUNK_SIZE_I32 + radius * 2;
+        let origin = chunk_position * CHUNK_SIZE_I32 - IVec3::new(radius, 0, radius);
+
+        let colors: Vec<Vec3> = (0..size * size)
+            .map(|i| biome_color(seed, origin.x + i / size, origin.z + i % size))
+            .collect();
+
+        let mut tints = Self::uniform(Vec3::ZERO);
+        let samples = ((radius * 2 + 1) * (radius * 2 + 1)) as f32;
+        for x in 0..CHUNK_SIZE_I32 {
+            for z in 0..CHUNK_SIZE_I32 {
+                let mut sum = Vec3::ZERO;
+                for dx in 0..=radius * 2 {
+                    for dz in 0..=radius * 2 {
+                        sum += colors[((x + dx) * size + z + dz) as usize];
+                    }
+                }
+                tints.colors[(x * CHUNK_SIZE_I32 + z) as usize] = sum / samples;
+            }
+        }
+
+        tints
+    }
+
+    #[inline(always)]
+    pub fn get(&self, x: i32, z: i32) -> Vec3 {
+        self.colors[(x * CHUNK_SIZE_I32 + z) as usize]
+    }
+}
+
+#[test]
+fn blending_averages_neighbouring_columns() {
+    let chunk_position = IVec3::new(3, 0, -2);
+    let sharp = BiomeTints::new(11, chunk_position, 0);
+    let blended = BiomeTints::new(11, chunk_position, 1);
+
+    let (x, z) = (3 * 32 + 5, -2 * 32 + 7);
+    assert_eq!(sharp.get(5, 7), biome_color(11, x, z));
+
+    let mut sum = Vec3::ZERO;
+    for dx in -1..=1 {
+        for dz in -1..=1 {
+            sum += biome_color(11, x + dx, z + dz);
+        }
+    }
+    assert!((blended.get(5, 7) - sum / 9.0).abs().max_element() < 1e-5);
+}
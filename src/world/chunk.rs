@@ -1,9 +1,8 @@
 #![allow(unused)]
 
-use bevy::prelude::{IVec3, Vec3};
-use rand::Rng;
+use glam::{IVec3, Vec3};
 
-use crate::data::{constants::*, VoxelType};
+use crate::data::{block_state::BlockState, constants::*, VoxelType};
 
 use super::*;
 
@@ -31,19 +30,25 @@ impl Chunk {
         World::chunk_to_world_position(self.world_position)
     }
 
-    pub fn generate_at(world_position: IVec3) -> Option<Chunk> {
+    pub fn generate_at(world_position: IVec3, seed: u64) -> Option<Chunk> {
         let mut chunk = Chunk::new(world_position);
         let mut empty_chunk = true;
-        let mut rng = rand::thread_rng();
 
         let chunk_world_position = chunk.world_position().as_ivec3();
-        if chunk_world_position.y > 288 {
+        if chunk_world_position.y > generation::TERRAIN_MAX_CHUNK_HEIGHT {
             return Some(chunk);
         }
 
+        let mut heights = [0; CHUNK_SIZE * CHUNK_SIZE];
+        for (i, height) in heights.iter_mut().enumerate() {
+            let x = chunk_world_position.x + (i / CHUNK_SIZE) as i32;
+            let z = chunk_world_position.z + (i % CHUNK_SIZE) as i32;
+            *height = generation::terrain_height(seed, x, z);
+        }
+
         for voxel_position in chunk.iter_voxels() {
             let position = chunk_world_position + voxel_position;
-            let h = 16 + rng.gen_range(-4..=4);
+            let h = heights[voxel_position.x as usize * CHUNK_SIZE + voxel_position.z as usize];
             if position.y == h {
                 chunk.set_voxel(VoxelType::Grass, voxel_position);
                 empty_chunk = false;
@@ -72,9 +77,17 @@ impl Chunk {
         self.voxels.get_at(position).is_transparent()
     }
 
+    pub const fn get_state(&self, position: IVec3) -> BlockState {
+        self.voxels.state_at(position)
+    }
+
     pub fn set_voxel(&mut self, voxel: VoxelType, position: IVec3) {
         self.voxels.set_at(voxel, position)
     }
+
+    pub fn set_voxel_with_state(&mut self, voxel: VoxelType, state: BlockState, position: IVec3) {
+        self.voxels.set_with_state_at(voxel, state, position)
+    }
 }
 
 #[inline(always)]
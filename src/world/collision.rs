@@ -0,0 +1,320 @@
+use glam::*;
+
+use crate::data::{block_shape::BlockBox, block_state::BlockState};
+
+use super::World;
+
+const COLLISION_EPSILON: f32 = 1e-4;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub const fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+        Self::new(center - half_extents, center + half_extents)
+    }
+
+    // Voxels are centered on their integer position.
+    pub fn voxel(position: IVec3) -> Self {
+        Self::from_center_half_extents(position.as_vec3(), Vec3::splat(0.5))
+    }
+
+    // A box of a block's shape, rotated by its state and moved to the voxel at `position`.
+    pub fn block_box(block_box: &BlockBox, state: BlockState, position: IVec3) -> Self {
+        let [a, b] = [block_box.min, block_box.max]
+            .map(|corner| state.transform_point(corner) - 0.5 + position.as_vec3());
+        Self::new(a.min(b), a.max(b))
+    }
+
+    #[inline(always)]
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    #[inline(always)]
+    pub fn translate(&self, offset: Vec3) -> Self {
+        Self::new(self.min + offset, self.max + offset)
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|axis| overlaps_on_axis(self, other, axis))
+    }
+
+    // Inclusive range of voxel positions touched by this box.
+    pub fn voxel_bounds(&self) -> (IVec3, IVec3) {
+        (
+            (self.min + 0.5 + COLLISION_EPSILON).floor().as_ivec3(),
+            (self.max + 0.5 - COLLISION_EPSILON).floor().as_ivec3(),
+        )
+    }
+}
+
+#[inline(always)]
+fn overlaps_on_axis(a: &Aabb, b: &Aabb, axis: usize) -> bool {
+    a.min[axis] < b.max[axis] - COLLISION_EPSILON && a.max[axis] > b.min[axis] + COLLISION_EPSILON
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MoveResult {
+    pub offset: Vec3,
+    pub collided: BVec3,
+    pub on_ground: bool,
+}
+
+impl World {
+    // Solid voxels collide with the boxes of their shape, so slabs and fences only block the
+    // part of the voxel they fill.
+    pub fn collision_boxes(&self, position: IVec3) -> impl Iterator<Item = Aabb> {
+        let voxel_type = self.get_voxel(position);
+        let state = self.get_state(position);
+        let boxes = if voxel_type.is_solid() {
+            voxel_type.shape().boxes()
+        } else {
+            &[]
+        };
+
+        boxes
+            .iter()
+            .map(move |block_box| Aabb::block_box(block_box, state, position))
+    }
+
+    pub fn collides(&self, aabb: &Aabb) -> bool {
+        let (min, max) = aabb.voxel_bounds();
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    if self
+                        .collision_boxes(IVec3::new(x, y, z))
+                        .any(|voxel| aabb.intersects(&voxel))
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    // Clips `distance` along `axis` so that `aabb` stops at the first solid box in its path.
+    fn clip_axis(&self, aabb: &Aabb, axis: usize, distance: f32) -> f32 {
+        if distance == 0.0 {
+            return 0.0;
+        }
+
+        let mut offset = Vec3::ZERO;
+        offset[axis] = distance;
+
+        let swept = Aabb::new(
+            aabb.min.min(aabb.min + offset),
+            aabb.max.max(aabb.max + offset),
+        );
+
+        let (min, max) = swept.voxel_bounds();
+        let mut clipped = distance;
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    for voxel in self.collision_boxes(IVec3::new(x, y, z)) {
+                        let blocks_path = (0..3)
+                            .filter(|&other| other != axis)
+                            .all(|other| overlaps_on_axis(aabb, &voxel, other));
+
+                        if !blocks_path {
+                            continue;
+                        }
+
+                        if distance > 0.0 && voxel.min[axis] >= aabb.max[axis] - COLLISION_EPSILON {
+                            clipped = clipped.min(voxel.min[axis] - aabb.max[axis]);
+                        } else if distance < 0.0
+                            && voxel.max[axis] <= aabb.min[axis] + COLLISION_EPSILON
+                        {
+                            clipped = clipped.max(voxel.max[axis] - aabb.min[axis]);
+                        }
+                    }
+                }
+            }
+        }
+
+        clipped
+    }
+
+    // Moves `aabb` by `motion` one axis at a time (Y, X, Z), stopping at solid voxels.
+    pub fn sweep_aabb(&self, aabb: &Aabb, motion: Vec3) -> MoveResult {
+        let mut moved = *aabb;
+        let mut offset = Vec3::ZERO;
+        let mut collided = BVec3::FALSE;
+
+        for axis in [1, 0, 2] {
+            let distance = self.clip_axis(&moved, axis, motion[axis]);
+            if distance != motion[axis] {
+                match axis {
+                    0 => collided.x = true,
+                    1 => collided.y = true,
+                    _ => collided.z = true,
+                }
+            }
+
+            let mut axis_offset = Vec3::ZERO;
+            axis_offset[axis] = distance;
+            moved = moved.translate(axis_offset);
+            offset += axis_offset;
+        }
+
+        MoveResult {
+            offset,
+            collided,
+            on_ground: collided.y && motion.y < 0.0,
+        }
+    }
+
+    // Like `sweep_aabb`, but climbs obstacles up to `step_height` when horizontal movement is blocked.
+    pub fn move_aabb(&self, aabb: &Aabb, motion: Vec3, step_height: f32) -> MoveResult {
+        let result = self.sweep_aabb(aabb, motion);
+        if step_height <= 0.0 || !(result.collided.x || result.collided.z) {
+            return result;
+        }
+
+        let up = self.sweep_aabb(aabb, Vec3::new(0.0, step_height, 0.0));
+        let horizontal = Vec3::new(motion.x, 0.0, motion.z);
+        let across = self.sweep_aabb(&aabb.translate(up.offset), horizontal);
+        let raised = aabb.translate(up.offset + across.offset);
+        let down = self.sweep_aabb(&raised, Vec3::new(0.0, -up.offset.y, 0.0));
+
+        let stepped_distance = across.offset.xz().length_squared();
+        let direct_distance = result.offset.xz().length_squared();
+        if !down.on_ground || stepped_distance <= direct_distance + COLLISION_EPSILON {
+            return result;
+        }
+
+        MoveResult {
+            offset: up.offset + across.offset + down.offset,
+            collided: BVec3::new(across.collided.x, true, across.collided.z),
+            on_ground: true,
+        }
+    }
+}
+
+#[cfg(test)]
+fn flat_world() -> World {
+    use crate::{data::VoxelType, world::Chunk};
+
+    let mut world = World::with_seed(0);
+    for x in -1..=0 {
+        for z in -1..=0 {
+            world.set_chunk(IVec3::new(x, 0, z), Chunk::new(IVec3::new(x, 0, z)));
+        }
+    }
+
+    for x in -32..32 {
+        for z in -32..32 {
+            world.set_voxel(VoxelType::Stone, IVec3::new(x, 0, z));
+        }
+    }
+
+    world
+}
+
+#[cfg(test)]
+fn player_box(feet: Vec3) -> Aabb {
+    Aabb::new(
+        feet - Vec3::new(0.3, 0.0, 0.3),
+        feet + Vec3::new(0.3, 1.8, 0.3),
+    )
+}
+
+#[test]
+fn falling_stops_on_ground() {
+    let world = flat_world();
+    let result = world.sweep_aabb(
+        &player_box(Vec3::new(0.0, 3.0, 0.0)),
+        Vec3::new(0.0, -10.0, 0.0),
+    );
+
+    assert!(result.on_ground);
+    assert!((result.offset.y + 2.5).abs() < 1e-4);
+}
+
+#[test]
+fn fast_motion_does_not_tunnel() {
+    let world = flat_world();
+    let result = world.sweep_aabb(
+        &player_box(Vec3::new(0.0, 3.0, 0.0)),
+        Vec3::new(0.0, -500.0, 0.0),
+    );
+
+    assert!(result.on_ground);
+    assert!((result.offset.y + 2.5).abs() < 1e-4);
+}
+
+#[test]
+fn walls_block_horizontal_motion() {
+    use crate::data::VoxelType;
+
+    let mut world = flat_world();
+    world.set_voxel(VoxelType::Stone, IVec3::new(2, 1, 0));
+    world.set_voxel(VoxelType::Stone, IVec3::new(2, 2, 0));
+
+    let result = world.move_aabb(
+        &player_box(Vec3::new(0.0, 0.5, 0.0)),
+        Vec3::new(3.0, 0.0, 0.0),
+        1.0,
+    );
+
+    assert!(result.collided.x);
+    assert!((result.offset.x - 1.2).abs() < 1e-4);
+    assert_eq!(result.offset.y, 0.0);
+}
+
+#[test]
+fn steps_up_single_blocks() {
+    use crate::data::VoxelType;
+
+    let mut world = flat_world();
+    world.set_voxel(VoxelType::Stone, IVec3::new(2, 1, 0));
+
+    let feet = Vec3::new(0.0, 0.5, 0.0);
+    let motion = Vec3::new(1.5, -0.1, 0.0);
+
+    let result = world.move_aabb(&player_box(feet), motion, 1.0);
+    assert!(result.on_ground);
+    assert!((result.offset.x - 1.5).abs() < 1e-4);
+    assert!((result.offset.y - 1.0).abs() < 1e-4);
+
+    let result = world.move_aabb(&player_box(feet), motion, 0.0);
+    assert!(result.collided.x);
+    assert!((result.offset.x - 1.2).abs() < 1e-4);
+}
+
+#[test]
+fn stands_on_slabs_and_passes_beside_fence_posts() {
+    use crate::data::VoxelType;
+
+    let mut world = flat_world();
+    world.set_voxel(VoxelType::StoneSlab, IVec3::new(0, 1, 0));
+    world.set_voxel(VoxelType::Fence, IVec3::new(2, 1, 1));
+
+    // The slab's top is half way up the voxel above the ground, at y = 1.0.
+    let result = world.sweep_aabb(
+        &player_box(Vec3::new(0.0, 3.0, 0.0)),
+        Vec3::new(0.0, -10.0, 0.0),
+    );
+    assert!(result.on_ground);
+    assert!((result.offset.y + 2.0).abs() < 1e-4);
+
+    // Walking past the post without touching it, a full cube would have blocked the way.
+    let result = world.sweep_aabb(
+        &player_box(Vec3::new(0.0, 0.5, 1.5)),
+        Vec3::new(4.0, 0.0, 0.0),
+    );
+    assert!(!result.collided.x);
+}
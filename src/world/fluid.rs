@@ -0,0 +1,221 @@
+use std::collections::HashSet;
+
+use glam::IVec3;
+
+use crate::data::{block_state::BlockState, constants::LIQUID_SOURCE_LEVEL, VoxelType};
+
+use super::World;
+
+const HORIZONTAL_NEIGHBOURS: [IVec3; 4] = [
+    IVec3::new(1, 0, 0),
+    IVec3::new(-1, 0, 0),
+    IVec3::new(0, 0, 1),
+    IVec3::new(0, 0, -1),
+];
+
+// Cellular automaton for liquids. Only voxels near a change are updated each tick, the rest of
+// the world is left alone.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "render", derive(bevy::prelude::Resource))]
+pub struct FluidSimulation {
+    active: HashSet<IVec3>,
+}
+
+impl FluidSimulation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Wakes up the voxel at `position` and its neighbours.
+    pub fn schedule(&mut self, position: IVec3) {
+        self.active.insert(position);
+        for offset in HORIZONTAL_NEIGHBOURS {
+            self.active.insert(position + offset);
+        }
+        self.active.insert(position + IVec3::Y);
+        self.active.insert(position - IVec3::Y);
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    // Advances the simulation by one step and returns the positions that changed.
+    pub fn tick(&mut self, world: &mut World) -> Vec<IVec3> {
+        let active = std::mem::take(&mut self.active);
+
+        // Every update is computed from the same snapshot, so the order doesn't matter.
+        let changes: Vec<(IVec3, VoxelType, u8)> = active
+            .into_iter()
+            .filter(|position| world.chunk_exists(World::world_to_chunk_position(*position)))
+            .filter_map(|position| next_state(world, position).map(|(v, l)| (position, v, l)))
+            .collect();
+
+        for (position, voxel, level) in &changes {
+            world.set_voxel_with_state(*voxel, BlockState::DEFAULT.with_level(*level), *position);
+            self.schedule(*position);
+        }
+
+        changes
+            .into_iter()
+            .map(|(position, _, _)| position)
+            .collect()
+    }
+}
+
+// Liquid flowing into `position`, if any.
+fn incoming_liquid(world: &World, position: IVec3, current: VoxelType) -> Option<(VoxelType, u8)> {
+    let accepts = |liquid: VoxelType| current == VoxelType::Air || current == liquid;
+
+    let above = world.get_voxel(position + IVec3::Y);
+    if above.is_liquid() && accepts(above) {
+        return Some((above, LIQUID_SOURCE_LEVEL - 1));
+    }
+
+    let mut best: Option<(VoxelType, u8)> = None;
+    let mut water_sources = 0;
+
+    for offset in HORIZONTAL_NEIGHBOURS {
+        let neighbour_position = position + offset;
+        let neighbour = world.get_voxel(neighbour_position);
+        if !neighbour.is_liquid() || !accepts(neighbour) {
+            continue;
+        }
+
+        let level = world.get_level(neighbour_position);
+        if neighbour == VoxelType::Water && level == LIQUID_SOURCE_LEVEL {
+            water_sources += 1;
+        }
+
+        // Liquid only spreads sideways once it has something to rest on.
+        let below = world.get_voxel(neighbour_position - IVec3::Y);
+        if level != LIQUID_SOURCE_LEVEL && below == VoxelType::Air {
+            continue;
+        }
+
+        let spread_level = level.saturating_sub(neighbour.liquid_decay());
+        if spread_level > 0 && best.is_none_or(|(_, best_level)| spread_level > best_level) {
+            best = Some((neighbour, spread_level));
+        }
+    }
+
+    // Water between two sources becomes a source itself, as long as it doesn't drain away.
+    if water_sources >= 2 && accepts(VoxelType::Water) {
+        let below = world.get_voxel(position - IVec3::Y);
+        let below_level = world.get_level(position - IVec3::Y);
+        if below.is_solid() || (below == VoxelType::Water && below_level == LIQUID_SOURCE_LEVEL) {
+            return Some((VoxelType::Water, LIQUID_SOURCE_LEVEL));
+        }
+    }
+
+    best
+}
+
+fn next_state(world: &World, position: IVec3) -> Option<(VoxelType, u8)> {
+    let current = world.get_voxel(position);
+    let level = world.get_level(position);
+
+    if current.is_solid() || (current.is_liquid() && level == LIQUID_SOURCE_LEVEL) {
+        return None;
+    }
+
+    match incoming_liquid(world, position, current) {
+        Some((liquid, new_level)) if liquid != current || new_level != level => {
+            Some((liquid, new_level))
+        }
+        Some(_) => None,
+        None if current.is_liquid() => Some((VoxelType::Air, 0)),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+fn flat_world(chunks: &[IVec3]) -> World {
+    use super::Chunk;
+
+    let mut world = World::with_seed(0);
+    for chunk_position in chunks {
+        let mut chunk = Chunk::new(*chunk_position);
+        if chunk_position.y == 0 {
+            for position in chunk.iter_voxels().filter(|position| position.y == 0) {
+                chunk.set_voxel(VoxelType::Stone, position);
+            }
+        }
+        world.set_chunk(*chunk_position, chunk);
+    }
+    world
+}
+
+#[cfg(test)]
+fn run_until_idle(simulation: &mut FluidSimulation, world: &mut World) {
+    for _ in 0..100 {
+        if simulation.is_idle() {
+            return;
+        }
+        simulation.tick(world);
+    }
+    panic!("the fluid simulation didn't settle");
+}
+
+#[test]
+fn water_spreads_with_decreasing_levels() {
+    let mut world = flat_world(&[IVec3::ZERO]);
+    let mut simulation = FluidSimulation::new();
+
+    let source = IVec3::new(16, 1, 16);
+    world.set_voxel(VoxelType::Water, source);
+    simulation.schedule(source);
+    run_until_idle(&mut simulation, &mut world);
+
+    for distance in 1..LIQUID_SOURCE_LEVEL as i32 {
+        let position = source + IVec3::X * distance;
+        assert_eq!(world.get_voxel(position), VoxelType::Water);
+        assert_eq!(
+            world.get_level(position),
+            LIQUID_SOURCE_LEVEL - distance as u8
+        );
+    }
+    assert_eq!(
+        world.get_voxel(source + IVec3::X * LIQUID_SOURCE_LEVEL as i32),
+        VoxelType::Air
+    );
+}
+
+#[test]
+fn liquid_falls_and_drains_when_the_source_is_removed() {
+    let mut world = flat_world(&[IVec3::ZERO]);
+    let mut simulation = FluidSimulation::new();
+
+    let source = IVec3::new(16, 8, 16);
+    world.set_voxel(VoxelType::Lava, source);
+    simulation.schedule(source);
+    run_until_idle(&mut simulation, &mut world);
+
+    assert_eq!(world.get_voxel(IVec3::new(16, 1, 16)), VoxelType::Lava);
+    assert_eq!(world.get_voxel(IVec3::new(17, 1, 16)), VoxelType::Lava);
+
+    world.set_voxel(VoxelType::Air, source);
+    simulation.schedule(source);
+    run_until_idle(&mut simulation, &mut world);
+
+    for position in world.get_chunk(IVec3::ZERO).unwrap().iter_voxels() {
+        assert!(!world.get_voxel(position).is_liquid());
+    }
+}
+
+#[test]
+fn water_flows_across_chunk_borders() {
+    let mut world = flat_world(&[IVec3::ZERO, IVec3::X]);
+    let mut simulation = FluidSimulation::new();
+
+    let source = IVec3::new(30, 1, 4);
+    world.set_voxel(VoxelType::Water, source);
+    simulation.schedule(source);
+    run_until_idle(&mut simulation, &mut world);
+
+    assert_eq!(world.get_voxel(IVec3::new(33, 1, 4)), VoxelType::Water);
+    assert_eq!(
+        world.get_level(IVec3::new(33, 1, 4)),
+        LIQUID_SOURCE_LEVEL - 3
+    );
+}
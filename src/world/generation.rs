@@ -0,0 +1,37 @@
+use glam::IVec3;
+
+pub const TERRAIN_BASE_HEIGHT: i32 = 16;
+pub const TERRAIN_HEIGHT_VARIATION: i32 = 4;
+
+// Chunks above this height are always empty.
+pub const TERRAIN_MAX_CHUNK_HEIGHT: i32 = 288;
+
+#[inline(always)]
+const fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+pub const fn column_hash(seed: u64, x: i32, z: i32) -> u64 {
+    splitmix64(seed ^ splitmix64(((x as u32 as u64) << 32) | z as u32 as u64))
+}
+
+pub const fn position_hash(seed: u64, position: IVec3) -> u64 {
+    splitmix64(column_hash(seed, position.x, position.z) ^ position.y as u32 as u64)
+}
+
+pub const fn terrain_height(seed: u64, x: i32, z: i32) -> i32 {
+    let range = (TERRAIN_HEIGHT_VARIATION * 2 + 1) as u64;
+    TERRAIN_BASE_HEIGHT + (column_hash(seed, x, z) % range) as i32 - TERRAIN_HEIGHT_VARIATION
+}
+
+#[test]
+fn terrain_height_is_deterministic_and_bounded() {
+    for (x, z) in [(0, 0), (-17, 4), (1024, -3000)] {
+        let h = terrain_height(42, x, z);
+        assert_eq!(h, terrain_height(42, x, z));
+        assert!((h - TERRAIN_BASE_HEIGHT).abs() <= TERRAIN_HEIGHT_VARIATION);
+    }
+}
@@ -1,19 +1,86 @@
-use bevy::{
-    math::*,
-    render::{mesh::Indices, mesh::Mesh, render_resource::PrimitiveTopology},
-};
+use glam::*;
 
-use crate::{
-    data::{constants::CHUNK_SIZE, voxel_face::*, *},
-    rendering::ChunkMaterial,
-};
+use crate::data::{block_shape::*, block_state::BlockState, constants::*, voxel_face::*, *};
 
-use super::Chunk;
+use super::{generation::position_hash, BiomeTints, Chunk, World};
 
+const BORDER_SIZE: usize = CHUNK_SIZE * CHUNK_SIZE;
+
+// Texture variants and rotations don't depend on the world seed, so a block keeps its look when
+// it's copied to another world.
+const TEXTURE_HASH_SEED: u64 = 0x7e57_a11a_5ca1_ab1e;
+
+type Border = [(VoxelType, BlockState); BORDER_SIZE];
+
+// The layer of voxels from each neighbouring chunk that touches this chunk, indexed by the face
+// it borders. Neighbours that aren't loaded are treated as air.
 #[derive(Debug, Clone, Default)]
-struct MeshData {
+pub struct ChunkNeighbours {
+    borders: [Option<Box<Border>>; 6],
+}
+
+impl ChunkNeighbours {
+    pub fn from_world(world: &World, chunk_position: IVec3) -> Self {
+        let mut neighbours = Self::default();
+        for face in FACES {
+            if let Some(chunk) = world.get_chunk(chunk_position + face.normal()) {
+                neighbours.set_border(face, chunk);
+            }
+        }
+        neighbours
+    }
+
+    // Copies the layer of `chunk` that touches the `face` side of the chunk being meshed.
+    pub fn set_border(&mut self, face: VoxelFace, chunk: &Chunk) {
+        let mut border = Box::new([(VoxelType::Air, BlockState::DEFAULT); BORDER_SIZE]);
+        let offset = face.normal() * CHUNK_SIZE_I32;
+
+        for a in 0..CHUNK_SIZE_I32 {
+            for b in 0..CHUNK_SIZE_I32 {
+                let outside = border_position(face, a, b);
+                let position = outside - offset;
+                border[(a * CHUNK_SIZE_I32 + b) as usize] =
+                    (chunk.get_voxel(position), chunk.get_state(position));
+            }
+        }
+
+        self.borders[face as usize] = Some(border);
+    }
+
+    // `position` is local to the chunk being meshed and just outside of it through `face`.
+    fn get(&self, face: VoxelFace, position: IVec3) -> (VoxelType, BlockState) {
+        let Some(border) = &self.borders[face as usize] else {
+            return (VoxelType::Air, BlockState::DEFAULT);
+        };
+
+        let (a, b) = match face {
+            VoxelFace::Left | VoxelFace::Right => (position.y, position.z),
+            VoxelFace::Bottom | VoxelFace::Top => (position.x, position.z),
+            VoxelFace::Back | VoxelFace::Front => (position.x, position.y),
+        };
+        border[(a * CHUNK_SIZE_I32 + b) as usize]
+    }
+}
+
+// Position just outside of the chunk through `face`, with `a` and `b` being the two coordinates
+// along the face in x, y, z order.
+const fn border_position(face: VoxelFace, a: i32, b: i32) -> IVec3 {
+    match face {
+        VoxelFace::Left => IVec3::new(-1, a, b),
+        VoxelFace::Right => IVec3::new(CHUNK_SIZE_I32, a, b),
+        VoxelFace::Bottom => IVec3::new(a, -1, b),
+        VoxelFace::Top => IVec3::new(a, CHUNK_SIZE_I32, b),
+        VoxelFace::Back => IVec3::new(a, b, -1),
+        VoxelFace::Front => IVec3::new(a, b, CHUNK_SIZE_I32),
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MeshData {
     pub indices: Vec<u32>,
     pub positions: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub tints: Vec<[u8; 4]>,
     pub data: Vec<u32>,
 }
 
@@ -22,74 +89,600 @@ impl MeshData {
         MeshData {
             indices: Vec::new(),
             positions: Vec::new(),
+            uvs: Vec::new(),
+            tints: Vec::new(),
             data: Vec::new(),
         }
     }
+
+    #[inline(always)]
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    #[inline(always)]
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
 }
 
+#[cfg(feature = "render")]
+impl From<MeshData> for bevy::render::mesh::Mesh {
+    fn from(mesh_data: MeshData) -> Self {
+        use crate::rendering::ChunkMaterial;
+        use bevy::render::{
+            mesh::{Indices, Mesh, VertexAttributeValues},
+            render_resource::PrimitiveTopology,
+        };
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.set_indices(Some(Indices::U32(mesh_data.indices)));
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, mesh_data.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, mesh_data.uvs);
+        mesh.insert_attribute(
+            ChunkMaterial::ATTRIBUTE_TINT,
+            VertexAttributeValues::Unorm8x4(mesh_data.tints),
+        );
+        mesh.insert_attribute(ChunkMaterial::ATTRIBUTE_DATA, mesh_data.data);
+        mesh
+    }
+}
+
+// Frame times are stored in steps of 1/20th of a second.
+const FRAME_TIME_STEPS_PER_SECOND: f32 = 20.0;
+
+// Per-vertex data layout, from the lowest bit:
+// color intensity (3 bits), texture layer (8 bits), animation frame count - 1 (4 bits),
+// animation frame time (6 bits) and block light (4 bits).
 #[inline(always)]
-fn add_face(mesh_data: &mut MeshData, voxel_type: VoxelType, position: IVec3, face: VoxelFace) {
-    let indices_offset = mesh_data.positions.len() as u32;
-    let face_vertex_positions = face.vertex_positions();
-    let face_index = voxel_type.get_face_index(face);
-    let face_color_intensity = face.color_intensity();
+fn pack_vertex_data(
+    texture_index: u32,
+    animation: Option<TextureAnimation>,
+    color_intensity: u32,
+    block_light: u8,
+) -> u32 {
+    let (frames, frame_time) = animation.map_or((1, 0), |animation| {
+        let steps = (animation.frame_time * FRAME_TIME_STEPS_PER_SECOND).round() as u32;
+        (animation.frames.clamp(1, 16), steps.clamp(1, 63))
+    });
+
+    ((block_light.min(15) as u32) << 21)
+        | (frame_time << 15)
+        | ((frames - 1) << 11)
+        | (texture_index << 3)
+        | color_intensity
+}
 
-    for i in 0..6 {
-        mesh_data.indices.push(indices_offset + FACE_INDICES[i]);
+const NO_TINT: [u8; 4] = [255; 4];
 
-        if i >= 4 {
-            continue;
+// The voxel being meshed.
+#[derive(Debug, Copy, Clone)]
+struct MeshedVoxel {
+    voxel_type: VoxelType,
+    state: BlockState,
+    position: IVec3,
+    // Picks texture variants and rotations.
+    hash: u64,
+    // Biome colour of the column, applied to faces that are tinted.
+    tint: Vec3,
+}
+
+impl MeshedVoxel {
+    fn face_tint(&self, face: VoxelFace) -> [u8; 4] {
+        if !self.voxel_type.is_tinted(face) {
+            return NO_TINT;
         }
 
-        let face_position = face_vertex_positions[i] + position.as_vec3();
-        mesh_data.positions.push(face_position.into());
+        let [r, g, b] = (self.tint.clamp(Vec3::ZERO, Vec3::ONE) * 255.0)
+            .round()
+            .to_array()
+            .map(|channel| channel as u8);
+        [r, g, b, 255]
+    }
+}
 
-        let uvx = FACE_UVS[i].x as u32;
-        let uvy = FACE_UVS[i].y as u32;
+#[inline(always)]
+fn add_quad(
+    mesh_data: &mut MeshData,
+    corners: [Vec3; 4],
+    uvs: [Vec2; 4],
+    data: u32,
+    tint: [u8; 4],
+) {
+    let indices_offset = mesh_data.positions.len() as u32;
+    mesh_data
+        .indices
+        .extend(FACE_INDICES.iter().map(|index| indices_offset + index));
 
-        let data: u32 = (face_index << 6) | (face_color_intensity << 2) | (uvx << 1) | uvy;
+    for (corner, uv) in corners.into_iter().zip(uvs) {
+        mesh_data.positions.push(corner.into());
+        mesh_data.uvs.push(uv.into());
+        mesh_data.tints.push(tint);
         mesh_data.data.push(data);
     }
 }
 
+// Adds the `face` side of `block_box`, both in block space. The texture is cropped to the part
+// of the face the box covers, so a slab shows the lower half of its side texture. Geometry and
+// textures are then rotated to world space together.
 #[inline(always)]
-pub fn generate_chunk_mesh(chunk: &Chunk) -> Mesh {
-    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+fn add_face(mesh_data: &mut MeshData, voxel: &MeshedVoxel, face: VoxelFace, block_box: &BlockBox) {
+    let MeshedVoxel {
+        voxel_type,
+        state,
+        position,
+        hash,
+        ..
+    } = *voxel;
 
-    let mut mesh_data: MeshData = MeshData::new();
+    let full_corners = face.vertex_positions().map(|corner| corner + 0.5);
+    let u_axis = full_corners[1] - full_corners[0];
+    let v_axis = full_corners[2] - full_corners[0];
+
+    let box_corners = full_corners
+        .map(|corner| Vec3::select(corner.cmpgt(Vec3::ZERO), block_box.max, block_box.min));
+    let rotation = if voxel_type.has_random_rotation(face) {
+        (hash >> 32) % 4
+    } else {
+        0
+    };
+    let uvs = box_corners.map(|corner| {
+        let offset = corner - full_corners[0];
+        let mut uv = Vec2::new(offset.dot(u_axis), offset.dot(v_axis));
+        for _ in 0..rotation {
+            uv = Vec2::new(1.0 - uv.y, uv.x);
+        }
+        uv
+    });
+    let corners =
+        box_corners.map(|corner| state.transform_point(corner) - 0.5 + position.as_vec3());
+
+    add_quad(
+        mesh_data,
+        corners,
+        uvs,
+        pack_vertex_data(
+            voxel_type.face_texture(face, hash),
+            voxel_type.texture_animation(),
+            state.to_world_face(face).color_intensity(),
+            voxel_type.light_emission(),
+        ),
+        voxel.face_tint(face),
+    );
+}
+
+// Two diagonal quads through the voxel, added with both windings so they can be seen from
+// either side.
+fn add_cross(mesh_data: &mut MeshData, voxel: &MeshedVoxel) {
+    let voxel_type = voxel.voxel_type;
+    let center = voxel.position.as_vec3();
+    let diagonals = [
+        (Vec3::new(-0.5, 0.0, -0.5), Vec3::new(0.5, 0.0, 0.5)),
+        (Vec3::new(-0.5, 0.0, 0.5), Vec3::new(0.5, 0.0, -0.5)),
+    ];
+
+    for (a, b) in diagonals {
+        for (left, right) in [(a, b), (b, a)] {
+            let corners = [
+                center + left + Vec3::Y * 0.5,
+                center + right + Vec3::Y * 0.5,
+                center + left - Vec3::Y * 0.5,
+                center + right - Vec3::Y * 0.5,
+            ];
+            add_quad(
+                mesh_data,
+                corners,
+                FACE_UVS,
+                pack_vertex_data(
+                    voxel_type.get_face_index(VoxelFace::Front),
+                    voxel_type.texture_animation(),
+                    VoxelFace::Front.color_intensity(),
+                    voxel_type.light_emission(),
+                ),
+                voxel.face_tint(VoxelFace::Front),
+            );
+        }
+    }
+}
+
+#[inline(always)]
+fn neighbour_voxel(
+    chunk: &Chunk,
+    neighbours: &ChunkNeighbours,
+    position: IVec3,
+    face: VoxelFace,
+) -> (VoxelType, BlockState) {
+    voxel_at(chunk, neighbours, position + face.normal())
+}
+
+// `position` is local to the chunk being meshed and may be just outside of it. Voxels outside
+// through more than one face, like diagonal neighbours, aren't available and are treated as air.
+fn voxel_at(
+    chunk: &Chunk,
+    neighbours: &ChunkNeighbours,
+    position: IVec3,
+) -> (VoxelType, BlockState) {
+    let mut crossed = FACES.into_iter().filter(|face| match face {
+        VoxelFace::Left => position.x < 0,
+        VoxelFace::Right => position.x >= CHUNK_SIZE_I32,
+        VoxelFace::Bottom => position.y < 0,
+        VoxelFace::Top => position.y >= CHUNK_SIZE_I32,
+        VoxelFace::Back => position.z < 0,
+        VoxelFace::Front => position.z >= CHUNK_SIZE_I32,
+    });
+
+    match (crossed.next(), crossed.next()) {
+        (None, _) => (chunk.get_voxel(position), chunk.get_state(position)),
+        (Some(face), None) => neighbours.get(face, position),
+        _ => (VoxelType::Air, BlockState::DEFAULT),
+    }
+}
+
+// Liquid surfaces follow the flow level, unless more of the same liquid sits on top.
+fn liquid_height(
+    chunk: &Chunk,
+    neighbours: &ChunkNeighbours,
+    position: IVec3,
+    (voxel_type, state): (VoxelType, BlockState),
+) -> f32 {
+    if voxel_at(chunk, neighbours, position + IVec3::Y).0 == voxel_type {
+        1.0
+    } else {
+        state.level() as f32 / (LIQUID_SOURCE_LEVEL + 1) as f32
+    }
+}
+
+// Each render layer gets its own mesh so it can be drawn with a different alpha mode.
+#[derive(Debug, Clone, Default)]
+pub struct ChunkMeshData {
+    pub opaque: MeshData,
+    pub cutout: MeshData,
+    pub translucent: MeshData,
+}
+
+impl ChunkMeshData {
+    // Translucent vertices are relative to the chunk centre instead of its corner, so the
+    // renderer sorts translucent chunks back to front by their centres.
+    pub const TRANSLUCENT_ORIGIN: Vec3 = Vec3::splat(CHUNK_SIZE as f32 / 2.0 - 0.5);
+
+    pub fn layer_mut(&mut self, layer: RenderLayer) -> &mut MeshData {
+        match layer {
+            RenderLayer::Opaque => &mut self.opaque,
+            RenderLayer::Cutout => &mut self.cutout,
+            RenderLayer::Translucent => &mut self.translucent,
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.opaque.vertex_count() + self.cutout.vertex_count() + self.translucent.vertex_count()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.opaque.triangle_count()
+            + self.cutout.triangle_count()
+            + self.translucent.triangle_count()
+    }
+}
+
+// A face on the `face` side of a voxel is hidden by a neighbour with a full face on that side.
+// Faces between two voxels of the same type are never drawn, so glass panes and bodies of water
+// don't show their inner faces.
+#[inline(always)]
+fn is_face_hidden(
+    voxel_type: VoxelType,
+    (neighbour, neighbour_state): (VoxelType, BlockState),
+    face: VoxelFace,
+) -> bool {
+    let neighbour_face = neighbour_state.to_local_face(face.opposite());
+    neighbour.shape().is_face_full(neighbour_face)
+        && (!neighbour.is_transparent() || neighbour == voxel_type)
+}
+
+pub fn generate_chunk_mesh_data(
+    chunk: &Chunk,
+    neighbours: &ChunkNeighbours,
+    tints: &BiomeTints,
+) -> ChunkMeshData {
+    let mut chunk_mesh_data = ChunkMeshData::default();
 
     for position in chunk.iter_voxels() {
         let voxel_type = chunk.get_voxel(position);
-        if voxel_type.is_transparent() {
+        if voxel_type == VoxelType::Air {
             continue;
         }
-    
-        for face in FACES {
-            if chunk.is_transparent_at(position + face.normal()) {
-                add_face(&mut mesh_data, voxel_type, position, face);
+
+        let state = chunk.get_state(position);
+        let world_position = chunk.position() * CHUNK_SIZE_I32 + position;
+        let voxel = MeshedVoxel {
+            voxel_type,
+            state,
+            position,
+            hash: position_hash(TEXTURE_HASH_SEED, world_position),
+            tint: tints.get(position.x, position.z),
+        };
+
+        let height = if voxel_type.is_liquid() {
+            liquid_height(chunk, neighbours, position, (voxel_type, state))
+        } else {
+            1.0
+        };
+
+        let mesh_data = chunk_mesh_data.layer_mut(voxel_type.render_layer());
+        let shape = voxel_type.shape();
+        if shape == BlockShape::Cross {
+            add_cross(mesh_data, &voxel);
+            continue;
+        }
+
+        for block_box in shape.boxes() {
+            let block_box = BlockBox::new(
+                block_box.min,
+                block_box.max.min(Vec3::new(1.0, height, 1.0)),
+            );
+            for face in FACES {
+                // Faces inside the voxel are always drawn.
+                let world_face = state.to_world_face(face);
+                let neighbour = neighbour_voxel(chunk, neighbours, position, world_face);
+                let mut face_box = block_box;
+                if block_box.touches_face(face) && is_face_hidden(voxel_type, neighbour, world_face)
+                {
+                    // Next to a lower surface of the same liquid, the side above it stays visible.
+                    let neighbour_height = if voxel_type.is_liquid()
+                        && neighbour.0 == voxel_type
+                        && world_face.is_side_face()
+                    {
+                        liquid_height(chunk, neighbours, position + world_face.normal(), neighbour)
+                    } else {
+                        1.0
+                    };
+
+                    if neighbour_height >= face_box.max.y {
+                        continue;
+                    }
+                    face_box.min.y = neighbour_height;
+                }
+
+                add_face(mesh_data, &voxel, face, &face_box);
             }
         }
     }
 
-    mesh.set_indices(Some(Indices::U32(mesh_data.indices)));
-    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, mesh_data.positions);
-    mesh.insert_attribute(ChunkMaterial::ATTRIBUTE_DATA, mesh_data.data);
-    mesh
+    for position in &mut chunk_mesh_data.translucent.positions {
+        *position = (Vec3::from(*position) - ChunkMeshData::TRANSLUCENT_ORIGIN).into();
+    }
+
+    chunk_mesh_data
 }
 
-pub fn generate_empty_chunk_mesh() -> Mesh {
-    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+#[cfg(feature = "render")]
+pub fn generate_empty_chunk_mesh() -> bevy::render::mesh::Mesh {
+    MeshData::default().into()
+}
 
-    let mesh_data: MeshData = MeshData::default();
+#[test]
+fn single_voxel_mesh_data() {
+    let mut chunk = Chunk::new((0, 0, 0).into());
+    chunk.set_voxel(VoxelType::Stone, IVec3::new(4, 4, 4));
 
-    mesh.set_indices(Some(Indices::U32(mesh_data.indices)));
-    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, mesh_data.positions);
-    mesh.insert_attribute(ChunkMaterial::ATTRIBUTE_DATA, mesh_data.data);
-    mesh
+    let mesh_data =
+        generate_chunk_mesh_data(&chunk, &ChunkNeighbours::default(), &BiomeTints::default());
+    assert_eq!(mesh_data.vertex_count(), 24);
+    assert_eq!(mesh_data.triangle_count(), 12);
+    assert_eq!(mesh_data.opaque.data.len(), 24);
 }
 
+#[test]
+fn neighbour_borders_hide_faces() {
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    chunk.set_voxel(VoxelType::Stone, IVec3::new(CHUNK_SIZE_I32 - 1, 4, 7));
+
+    let mut neighbour = Chunk::new(IVec3::X);
+    neighbour.set_voxel(VoxelType::Stone, IVec3::new(0, 4, 7));
+
+    let mut neighbours = ChunkNeighbours::default();
+    neighbours.set_border(VoxelFace::Right, &neighbour);
+
+    let mesh_data = generate_chunk_mesh_data(&chunk, &neighbours, &BiomeTints::default());
+    assert_eq!(mesh_data.triangle_count(), 10);
+}
+
+#[test]
+fn liquid_surface_follows_level() {
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    chunk.set_voxel(VoxelType::Stone, IVec3::new(4, 3, 4));
+    chunk.set_voxel_with_state(
+        VoxelType::Water,
+        BlockState::DEFAULT.with_level(4),
+        IVec3::new(4, 4, 4),
+    );
+    chunk.set_voxel(VoxelType::Water, IVec3::new(5, 4, 4));
+
+    let mesh_data =
+        generate_chunk_mesh_data(&chunk, &ChunkNeighbours::default(), &BiomeTints::default());
+    assert_eq!(mesh_data.opaque.vertex_count(), 24);
+
+    // Shared and solid faces are culled, except for the part of the source's side above the
+    // lower surface: 4 + 6 faces remain.
+    assert_eq!(mesh_data.translucent.triangle_count(), 20);
+
+    let top =
+        4.0 - 0.5 + 4.0 / (LIQUID_SOURCE_LEVEL + 1) as f32 - ChunkMeshData::TRANSLUCENT_ORIGIN.y;
+    assert!(mesh_data
+        .translucent
+        .positions
+        .iter()
+        .any(|p| (p[1] - top).abs() < 1e-5));
+}
+
+#[test]
+fn liquid_side_faces_show_above_lower_surfaces() {
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    chunk.set_voxel(VoxelType::Water, IVec3::new(4, 4, 4));
+    chunk.set_voxel_with_state(
+        VoxelType::Water,
+        BlockState::DEFAULT.with_level(4),
+        IVec3::new(5, 4, 4),
+    );
+    // The same liquid on top fills the source's other neighbour up to the top.
+    chunk.set_voxel_with_state(
+        VoxelType::Water,
+        BlockState::DEFAULT.with_level(4),
+        IVec3::new(3, 4, 4),
+    );
+    chunk.set_voxel(VoxelType::Water, IVec3::new(3, 5, 4));
+
+    let mesh_data =
+        generate_chunk_mesh_data(&chunk, &ChunkNeighbours::default(), &BiomeTints::default());
+
+    // Source: 4 faces plus its side above the level 4 cell. Level 4 cell: 5 faces, its side
+    // towards the source is culled. Filled cell: 4 faces plus its side above the source's
+    // surface. Water on top of it: 5 faces.
+    assert_eq!(mesh_data.translucent.triangle_count(), 40);
+
+    let lower_top = 4.0 / (LIQUID_SOURCE_LEVEL + 1) as f32;
+    let bottom = 4.0 - 0.5 + lower_top - ChunkMeshData::TRANSLUCENT_ORIGIN.y;
+    let side = 5.0 - 0.5 - ChunkMeshData::TRANSLUCENT_ORIGIN.x;
+    assert!(mesh_data
+        .translucent
+        .positions
+        .iter()
+        .any(|p| (p[0] - side).abs() < 1e-5 && (p[1] - bottom).abs() < 1e-5));
+}
+
+#[test]
+fn identical_transparent_voxels_share_no_faces() {
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    chunk.set_voxel(VoxelType::Glass, IVec3::new(4, 4, 4));
+    chunk.set_voxel(VoxelType::Glass, IVec3::new(5, 4, 4));
+    chunk.set_voxel(VoxelType::Leaves, IVec3::new(6, 4, 4));
+    chunk.set_voxel(VoxelType::Stone, IVec3::new(7, 4, 4));
+
+    let mesh_data =
+        generate_chunk_mesh_data(&chunk, &ChunkNeighbours::default(), &BiomeTints::default());
+    assert_eq!(mesh_data.translucent.triangle_count(), 20);
+    assert_eq!(mesh_data.cutout.triangle_count(), 10);
+    assert_eq!(mesh_data.opaque.triangle_count(), 12);
+}
+
+#[test]
+fn shapes_occlude_matching_faces() {
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    chunk.set_voxel(VoxelType::StoneSlab, IVec3::new(4, 4, 4));
+    chunk.set_voxel(VoxelType::Stone, IVec3::new(4, 3, 4));
+    chunk.set_voxel(VoxelType::Stone, IVec3::new(5, 4, 4));
+    chunk.set_voxel(VoxelType::TallGrass, IVec3::new(8, 4, 4));
+
+    let mesh_data =
+        generate_chunk_mesh_data(&chunk, &ChunkNeighbours::default(), &BiomeTints::default());
+
+    // The slab hides the top of the stone below it but not the side of the one next to it, and
+    // its own side facing the stone is hidden.
+    assert_eq!(mesh_data.opaque.triangle_count(), (4 + 5 + 6) * 2);
+    assert!(mesh_data.opaque.positions.iter().any(|p| p[1] == 4.0));
+    assert!(mesh_data.opaque.uvs.iter().any(|uv| uv[1] == 0.5));
+
+    // Plants are two quads, each with both windings.
+    assert_eq!(mesh_data.cutout.triangle_count(), 8);
+}
+
+#[test]
+fn block_state_rotates_shapes() {
+    use crate::data::block_state::Half;
+
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    let upside_down = VoxelType::StoneSlab.default_state().with_half(Half::Top);
+    chunk.set_voxel_with_state(VoxelType::StoneSlab, upside_down, IVec3::new(4, 4, 4));
+    chunk.set_voxel(VoxelType::Stone, IVec3::new(4, 5, 4));
+
+    let mesh_data =
+        generate_chunk_mesh_data(&chunk, &ChunkNeighbours::default(), &BiomeTints::default());
+    assert_eq!(mesh_data.opaque.triangle_count(), (5 + 5) * 2);
+
+    let lowest = mesh_data
+        .opaque
+        .positions
+        .iter()
+        .map(|p| p[1])
+        .fold(f32::MAX, f32::min);
+    assert_eq!(lowest, 4.0);
+}
+
+#[test]
+fn texture_variants_depend_on_world_position() {
+    let mut chunk = Chunk::new(IVec3::new(2, 0, -1));
+    for position in chunk.iter_voxels().filter(|position| position.y == 0) {
+        chunk.set_voxel(VoxelType::Stone, position);
+    }
+
+    let neighbours = ChunkNeighbours::default();
+    let mesh_data = generate_chunk_mesh_data(&chunk, &neighbours, &BiomeTints::default());
+    let layers: std::collections::HashSet<u32> = mesh_data
+        .opaque
+        .data
+        .iter()
+        .map(|data| (data >> 3) & 255)
+        .collect();
+    assert_eq!(layers.len(), 2);
+
+    let uvs: std::collections::HashSet<[u32; 2]> = mesh_data
+        .opaque
+        .uvs
+        .chunks(4)
+        .map(|uvs| [uvs[0][0] as u32, uvs[0][1] as u32])
+        .collect();
+    assert_eq!(uvs.len(), 4);
+
+    let remeshed = generate_chunk_mesh_data(&chunk, &neighbours, &BiomeTints::default());
+    assert_eq!(mesh_data.opaque.data, remeshed.opaque.data);
+    assert_eq!(mesh_data.opaque.uvs, remeshed.opaque.uvs);
+}
+
+#[test]
+fn animated_textures_are_packed_into_vertex_data() {
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    chunk.set_voxel(VoxelType::Lava, IVec3::new(4, 4, 4));
+
+    let mesh_data =
+        generate_chunk_mesh_data(&chunk, &ChunkNeighbours::default(), &BiomeTints::default());
+    let data = mesh_data.opaque.data[0];
+    assert_eq!((data >> 3) & 255, 20);
+    assert_eq!(((data >> 11) & 15) + 1, 4);
+    assert_eq!((data >> 15) & 63, 12);
+    assert_eq!(data >> 21, 15);
+}
+
+#[test]
+fn biome_tint_only_applies_to_tinted_faces() {
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    chunk.set_voxel(VoxelType::Grass, IVec3::new(4, 4, 4));
+
+    let tints = BiomeTints::uniform(Vec3::new(0.2, 0.6, 0.4));
+    let mesh_data = generate_chunk_mesh_data(&chunk, &ChunkNeighbours::default(), &tints);
+
+    let tinted = mesh_data
+        .opaque
+        .tints
+        .iter()
+        .filter(|tint| **tint == [51, 153, 102, 255])
+        .count();
+    assert_eq!(tinted, 4);
+    assert_eq!(mesh_data.opaque.tints.len(), 24);
+}
+
+#[cfg(feature = "render")]
 #[test]
 fn t() {
+    use crate::rendering::ChunkMaterial;
+    use bevy::render::{
+        mesh::{Indices, Mesh, VertexAttributeValues},
+        render_resource::PrimitiveTopology,
+    };
+
     let mut chunk = Chunk::new((0, 0, 0).into());
     for position in chunk.iter_voxels() {
         chunk.set_voxel(VoxelType::Dirt, position);
@@ -99,10 +692,16 @@ fn t() {
     let mut mesh_data: MeshData = MeshData::new();
 
     for position in chunk.iter_voxels() {
-        let voxel_type = chunk.get_voxel(position);
+        let voxel = MeshedVoxel {
+            voxel_type: chunk.get_voxel(position),
+            state: BlockState::DEFAULT,
+            position,
+            hash: 0,
+            tint: Vec3::ONE,
+        };
 
         for face in FACES {
-            add_face(&mut mesh_data, voxel_type, position, face);
+            add_face(&mut mesh_data, &voxel, face, &BlockBox::FULL);
         }
     }
 
@@ -112,5 +711,10 @@ fn t() {
 
     mesh.set_indices(Some(Indices::U32(mesh_data.indices)));
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, mesh_data.positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, mesh_data.uvs);
+    mesh.insert_attribute(
+        ChunkMaterial::ATTRIBUTE_TINT,
+        VertexAttributeValues::Unorm8x4(mesh_data.tints),
+    );
     mesh.insert_attribute(ChunkMaterial::ATTRIBUTE_DATA, mesh_data.data);
 }
@@ -1,8 +1,17 @@
+mod biome;
 mod chunk;
+mod collision;
+mod fluid;
+pub mod generation;
 pub mod meshing;
+pub mod storage;
 mod voxel_map;
+#[allow(clippy::module_inception)]
 mod world;
 
+pub use biome::*;
 pub use chunk::*;
+pub use collision::*;
+pub use fluid::*;
 pub use voxel_map::*;
 pub use world::*;
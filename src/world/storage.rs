@@ -0,0 +1,178 @@
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use glam::IVec3;
+
+use crate::data::{block_state::BlockState, constants::CHUNK_SIZE_CUBED, VoxelType};
+
+use super::Chunk;
+
+const CHUNK_MAGIC: &[u8; 4] = b"VXLC";
+const CHUNK_FORMAT_VERSION: u8 = 3;
+const METADATA_FILE: &str = "world.meta";
+
+pub fn chunk_file_path(directory: &Path, position: IVec3) -> PathBuf {
+    directory.join(format!(
+        "c.{}.{}.{}.bin",
+        position.x, position.y, position.z
+    ))
+}
+
+// Voxels are stored run-length encoded in flattened index order as
+// (run length: u16 LE, voxel id: u8, block state: u16 LE) runs. Version 1 had no state and
+// version 2 only stored the liquid level in a single byte.
+pub fn encode_chunk(chunk: &Chunk) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(64);
+    bytes.extend_from_slice(CHUNK_MAGIC);
+    bytes.push(CHUNK_FORMAT_VERSION);
+
+    let mut push_run = |(voxel, state): (VoxelType, BlockState), length: u16| {
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.push(voxel.id());
+        bytes.extend_from_slice(&state.bits().to_le_bytes());
+    };
+
+    let mut run: Option<((VoxelType, BlockState), u16)> = None;
+    for position in chunk.iter_voxels() {
+        let voxel = (chunk.get_voxel(position), chunk.get_state(position));
+        run = match run {
+            Some((current, length)) if current == voxel && length < u16::MAX => {
+                Some((current, length + 1))
+            }
+            Some((current, length)) => {
+                push_run(current, length);
+                Some((voxel, 1))
+            }
+            None => Some((voxel, 1)),
+        };
+    }
+
+    if let Some((current, length)) = run {
+        push_run(current, length);
+    }
+
+    bytes
+}
+
+pub fn decode_chunk(position: IVec3, bytes: &[u8]) -> io::Result<Chunk> {
+    if bytes.len() < 5 || &bytes[..4] != CHUNK_MAGIC {
+        return Err(invalid_data("not a chunk file"));
+    }
+
+    let run_size = match bytes[4] {
+        1 => 3,
+        2 => 4,
+        CHUNK_FORMAT_VERSION => 5,
+        _ => return Err(invalid_data("unsupported chunk format version")),
+    };
+
+    let mut chunk = Chunk::new(position);
+    let mut voxels = chunk.iter_voxels();
+    let mut decoded = 0;
+
+    for run in bytes[5..].chunks(run_size) {
+        let (l0, l1, id) = match *run {
+            [l0, l1, id, ..] if run.len() == run_size => (l0, l1, id),
+            _ => return Err(invalid_data("truncated chunk data")),
+        };
+
+        let length = u16::from_le_bytes([l0, l1]) as usize;
+        let voxel = VoxelType::from_id(id).ok_or_else(|| invalid_data("unknown voxel type"))?;
+        let state = match *run {
+            [_, _, _, s0, s1] => BlockState::from_bits(u16::from_le_bytes([s0, s1])),
+            [_, _, _, level] => voxel.default_state().with_level(level),
+            _ => voxel.default_state(),
+        };
+
+        decoded += length;
+        if decoded > CHUNK_SIZE_CUBED {
+            return Err(invalid_data("too many voxels in chunk data"));
+        }
+
+        for voxel_position in voxels.by_ref().take(length) {
+            chunk.set_voxel_with_state(voxel, state, voxel_position);
+        }
+    }
+
+    if decoded != CHUNK_SIZE_CUBED {
+        return Err(invalid_data("too few voxels in chunk data"));
+    }
+
+    Ok(chunk)
+}
+
+pub fn save_chunk(directory: &Path, chunk: &Chunk) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+    fs::write(
+        chunk_file_path(directory, chunk.position()),
+        encode_chunk(chunk),
+    )
+}
+
+pub fn load_chunk(directory: &Path, position: IVec3) -> io::Result<Option<Chunk>> {
+    let mut file = match fs::File::open(chunk_file_path(directory, position)) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    let mut bytes = vec![];
+    file.read_to_end(&mut bytes)?;
+    decode_chunk(position, &bytes).map(Some)
+}
+
+pub fn save_seed(directory: &Path, seed: u64) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+    let mut file = fs::File::create(directory.join(METADATA_FILE))?;
+    writeln!(file, "seed={seed}")
+}
+
+pub fn load_seed(directory: &Path) -> io::Result<Option<u64>> {
+    let contents = match fs::read_to_string(directory.join(METADATA_FILE)) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("seed="))
+        .map(|seed| {
+            seed.trim()
+                .parse()
+                .map_err(|_| invalid_data("invalid seed"))
+        })
+        .transpose()
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[test]
+fn chunk_round_trip() {
+    use crate::data::block_state::{Facing, Half};
+
+    let mut chunk = Chunk::generate_at(IVec3::new(3, 0, -2), 7).unwrap();
+    chunk.set_voxel_with_state(
+        VoxelType::Water,
+        BlockState::DEFAULT.with_level(5),
+        IVec3::new(1, 30, 1),
+    );
+    chunk.set_voxel_with_state(
+        VoxelType::StoneStairs,
+        BlockState::DEFAULT
+            .with_facing(Facing::West)
+            .with_half(Half::Top),
+        IVec3::new(2, 30, 1),
+    );
+    let decoded = decode_chunk(chunk.position(), &encode_chunk(&chunk)).unwrap();
+
+    for position in chunk.iter_voxels() {
+        assert_eq!(chunk.get_voxel(position), decoded.get_voxel(position));
+        assert_eq!(chunk.get_state(position), decoded.get_state(position));
+    }
+}
@@ -1,6 +1,6 @@
-use bevy::prelude::IVec3;
+use glam::IVec3;
 
-use crate::data::{constants::*, VoxelType};
+use crate::data::{block_state::BlockState, constants::*, VoxelType};
 
 #[inline]
 pub const fn flatten(position: IVec3) -> usize {
@@ -40,12 +40,20 @@ impl Iterator for VoxelIterator {
 #[derive(Debug, Clone)]
 pub struct VoxelMap {
     data: [VoxelType; CHUNK_SIZE_CUBED],
+    states: [BlockState; CHUNK_SIZE_CUBED],
+}
+
+impl Default for VoxelMap {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl VoxelMap {
     pub const fn new() -> Self {
         Self {
             data: [VoxelType::Air; CHUNK_SIZE_CUBED],
+            states: [BlockState::DEFAULT; CHUNK_SIZE_CUBED],
         }
     }
 
@@ -71,9 +79,23 @@ impl VoxelMap {
         }
     }
 
+    pub const fn state_at(&self, position: IVec3) -> BlockState {
+        if !VoxelMap::is_within_bounds(position) {
+            BlockState::DEFAULT
+        } else {
+            self.states[flatten(position)]
+        }
+    }
+
     pub fn set_at(&mut self, voxel: VoxelType, position: IVec3) {
+        self.set_with_state_at(voxel, voxel.default_state(), position);
+    }
+
+    pub fn set_with_state_at(&mut self, voxel: VoxelType, state: BlockState, position: IVec3) {
         if VoxelMap::is_within_bounds(position) {
-            self.data[flatten(position)] = voxel;
+            let index = flatten(position);
+            self.data[index] = voxel;
+            self.states[index] = state;
         }
     }
 }
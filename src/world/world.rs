@@ -1,24 +1,21 @@
 #![allow(unused)]
-const EPSILON: f32 = -1e-6f32;
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
 
-use bevy::math::*;
+use glam::*;
 use itertools::iproduct;
 
-use crate::data::{
-    constants::*,
-    voxel_face::{VoxelFace, FACES},
-    VoxelType,
-};
+use crate::data::{block_state::BlockState, constants::*, voxel_face::VoxelFace, VoxelType};
 
-use super::Chunk;
+use super::{Aabb, Chunk, VoxelMap};
 
-#[derive(Debug, bevy::prelude::Resource)]
+#[derive(Debug)]
+#[cfg_attr(feature = "render", derive(bevy::prelude::Resource))]
 pub struct World {
     chunks: HashMap<IVec3, Chunk>,
+    seed: u64,
 }
 
 impl Default for World {
@@ -29,9 +26,37 @@ impl Default for World {
 
 impl World {
     pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
         Self {
             chunks: HashMap::new(),
+            seed,
+        }
+    }
+
+    #[inline(always)]
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn chunks_within_radius(center: IVec3, radius: i32, vertical_radius: i32) -> Vec<IVec3> {
+        let mut positions = vec![];
+
+        for x in -radius..=radius {
+            for z in -radius..=radius {
+                if x.pow(2) + z.pow(2) >= radius.pow(2) {
+                    continue;
+                }
+
+                for y in -vertical_radius..=vertical_radius {
+                    positions.push(center + IVec3::new(x, y, z));
+                }
+            }
         }
+
+        positions
     }
 
     #[inline(always)]
@@ -69,6 +94,11 @@ impl World {
         &mut self.chunks
     }
 
+    // Memory held by the voxel data of every loaded chunk, in bytes.
+    pub fn voxel_memory_usage(&self) -> usize {
+        self.chunks.len() * std::mem::size_of::<VoxelMap>()
+    }
+
     pub fn chunk_exists(&self, position: IVec3) -> bool {
         self.chunks.contains_key(&position)
     }
@@ -99,14 +129,38 @@ impl World {
         chunk.get_voxel(voxel_position)
     }
 
+    pub fn get_state(&self, position: IVec3) -> BlockState {
+        let chunk_position = World::world_to_chunk_position(position);
+        let Some(chunk) = self.get_chunk(chunk_position) else {
+            return BlockState::DEFAULT;
+        };
+
+        let voxel_position = World::world_to_chunk_voxel_position(position);
+        chunk.get_state(voxel_position)
+    }
+
+    #[inline(always)]
+    pub fn get_level(&self, position: IVec3) -> u8 {
+        self.get_state(position).level()
+    }
+
     pub fn set_voxel(&mut self, voxel_type: VoxelType, position: IVec3) {
+        self.set_voxel_with_state(voxel_type, voxel_type.default_state(), position);
+    }
+
+    pub fn set_voxel_with_state(
+        &mut self,
+        voxel_type: VoxelType,
+        state: BlockState,
+        position: IVec3,
+    ) {
         let chunk_position = World::world_to_chunk_position(position);
         let Some(mut chunk) = self.get_chunk_mut(chunk_position) else {
             return;
         };
 
         let voxel_position = World::world_to_chunk_voxel_position(position);
-        chunk.set_voxel(voxel_type, voxel_position);
+        chunk.set_voxel_with_state(voxel_type, state, voxel_position);
     }
 
     pub fn raytrace(&self, position: Vec3, direction: Vec3, range: f32) -> Option<RaytraceResult> {
@@ -122,45 +176,27 @@ impl World {
         for (x, y, z) in iproduct!(min.x..=max.x, min.y..=max.y, min.z..=max.z) {
             let voxel_position = IVec3::new(x, y, z);
             let voxel_type = self.get_voxel(voxel_position);
-            if voxel_type == VoxelType::Air {
+            if voxel_type == VoxelType::Air || voxel_type.is_liquid() {
                 continue;
             }
 
-            for face in FACES {
-                let normal = face.normal().as_vec3();
-                let divisor = Vec3::dot(normal, direction);
-
-                // Ignore back faces
-                if divisor >= EPSILON {
-                    continue;
-                }
-
-                let plane_normal = normal * normal;
-                let voxel_size = Vec3::splat(0.5);
-                let d = -(Vec3::dot(voxel_position.as_vec3(), plane_normal)
-                    + Vec3::dot(voxel_size, normal));
-                let numerator = Vec3::dot(plane_normal, position) + d;
-
-                let distance = f32::abs(-numerator / divisor);
-                let point = position + distance * direction;
-
-                if (point.x < (x as f32) - voxel_size.x + EPSILON
-                    || point.x > (x as f32) + voxel_size.x - EPSILON
-                    || point.y < (y as f32) - voxel_size.y + EPSILON
-                    || point.y > (y as f32) + voxel_size.y - EPSILON
-                    || point.z < (z as f32) - voxel_size.z + EPSILON
-                    || point.z > (z as f32) + voxel_size.z - EPSILON)
-                {
+            // Only the boxes of the block's shape can be hit, so the ray passes through the empty
+            // parts of slabs, stairs and plants.
+            let state = self.get_state(voxel_position);
+            for block_box in voxel_type.shape().hit_boxes() {
+                let hit_box = Aabb::block_box(block_box, state, voxel_position);
+                let Some((distance, face)) = intersect_box(position, direction, &hit_box) else {
                     continue;
-                }
+                };
 
                 if distance <= range && (result.is_none() || result.unwrap().distance > distance) {
                     result = Some(RaytraceResult {
                         distance,
                         face,
-                        point,
+                        point: position + distance * direction,
                         voxel_type,
                         voxel_position,
+                        hit_box,
                     });
                 }
             }
@@ -170,6 +206,42 @@ impl World {
     }
 }
 
+// Distance along the ray to where it enters the box and the face it enters through. Rays
+// starting inside the box don't hit it.
+fn intersect_box(position: Vec3, direction: Vec3, aabb: &Aabb) -> Option<(f32, VoxelFace)> {
+    let inverse = direction.recip();
+    let t1 = (aabb.min - position) * inverse;
+    let t2 = (aabb.max - position) * inverse;
+    let near = t1.min(t2);
+    let far = t1.max(t2);
+
+    let enter = near.max_element();
+    let exit = far.min_element();
+    if enter < 0.0 || enter > exit {
+        return None;
+    }
+
+    let face = if enter == near.x {
+        if direction.x > 0.0 {
+            VoxelFace::Left
+        } else {
+            VoxelFace::Right
+        }
+    } else if enter == near.y {
+        if direction.y > 0.0 {
+            VoxelFace::Bottom
+        } else {
+            VoxelFace::Top
+        }
+    } else if direction.z > 0.0 {
+        VoxelFace::Back
+    } else {
+        VoxelFace::Front
+    };
+
+    Some((enter, face))
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct RaytraceResult {
     pub face: VoxelFace,
@@ -177,4 +249,47 @@ pub struct RaytraceResult {
     pub voxel_position: IVec3,
     pub distance: f32,
     pub point: Vec3,
+    // The box of the block's shape that was hit, in world space.
+    pub hit_box: Aabb,
+}
+
+#[test]
+fn raytrace_only_hits_shape_boxes() {
+    let mut world = World::with_seed(0);
+    world.set_chunk(IVec3::ZERO, Chunk::new(IVec3::ZERO));
+    world.set_voxel(VoxelType::StoneSlab, IVec3::new(4, 4, 4));
+
+    // Through the empty upper half of the slab.
+    let above = world.raytrace(Vec3::new(2.0, 4.25, 4.0), Vec3::X, 8.0);
+    assert_eq!(above, None);
+
+    let side = world
+        .raytrace(Vec3::new(2.0, 3.75, 4.0), Vec3::X, 8.0)
+        .unwrap();
+    assert_eq!(side.voxel_position, IVec3::new(4, 4, 4));
+    assert_eq!(side.face, VoxelFace::Left);
+    assert!((side.distance - 1.5).abs() < 1e-5);
+
+    // From above, the top of the slab is hit halfway down the voxel.
+    let top = world
+        .raytrace(Vec3::new(4.0, 6.0, 4.0), Vec3::NEG_Y, 8.0)
+        .unwrap();
+    assert_eq!(top.face, VoxelFace::Top);
+    assert!((top.point.y - 4.0).abs() < 1e-5);
+
+    // The upside down slab fills the upper half instead.
+    world.set_voxel_with_state(
+        VoxelType::StoneSlab,
+        VoxelType::StoneSlab
+            .default_state()
+            .with_half(crate::data::block_state::Half::Top),
+        IVec3::new(4, 4, 4),
+    );
+    assert!(world
+        .raytrace(Vec3::new(2.0, 4.25, 4.0), Vec3::X, 8.0)
+        .is_some());
+    assert_eq!(
+        world.raytrace(Vec3::new(2.0, 3.75, 4.0), Vec3::X, 8.0),
+        None
+    );
 }